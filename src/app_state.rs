@@ -0,0 +1,97 @@
+use std::sync::Arc;
+use std::time::Duration;
+use axum::extract::FromRef;
+use crate::configuration::configuration_provider::AppConfig;
+use crate::request_clients::build_orionoid_request_client;
+use crate::request_clients::orionoid_client::orionoid_request_client::OrionoidRequestClient;
+use crate::request_clients::orionoid_client::search_cache::SearchCache;
+use crate::torznab;
+use crate::torznab::types::Config;
+
+/// Everything a running indexer needs, built once from a config and shared with every handler through axum `State`.
+#[derive(Clone)]
+pub struct AppState {
+    pub app_config: Arc<AppConfig>,
+    pub orionoid_client: Arc<OrionoidRequestClient>,
+    pub torznab_config: Arc<Config>,
+}
+
+impl AppState {
+    pub fn from_config(app_config: Arc<AppConfig>) -> anyhow::Result<Self> {
+        let orionoid_client = build_orionoid_request_client(&app_config)?;
+        Ok(Self::new(app_config, orionoid_client))
+    }
+
+    /// Builds the state around an already built Orionoid client, e.g. one pointed at another base url.
+    pub fn new(app_config: Arc<AppConfig>, orionoid_client: OrionoidRequestClient) -> Self {
+        let orionoid_client = Arc::new(orionoid_client);
+        let search_cache = SearchCache::new(
+            Duration::from_secs(app_config.search_cache_ttl_seconds),
+            app_config.search_cache_max_entries,
+        );
+        let torznab_config = Arc::new(torznab::torznab_config(orionoid_client.clone(), search_cache));
+
+        Self {
+            app_config,
+            orionoid_client,
+            torznab_config,
+        }
+    }
+}
+
+impl FromRef<AppState> for Arc<Config> {
+    fn from_ref(state: &AppState) -> Self {
+        state.torznab_config.clone()
+    }
+}
+
+impl FromRef<AppState> for Arc<OrionoidRequestClient> {
+    fn from_ref(state: &AppState) -> Self {
+        state.orionoid_client.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::configuration::configuration_provider::ConfigurationProvider;
+
+    async fn serve(state: AppState) -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, torznab::torznab_router(state)).await });
+        format!("http://{address}")
+    }
+
+    fn app_config(response_store_path: &std::path::Path) -> Arc<AppConfig> {
+        Arc::new(AppConfig {
+            response_store_path: Some(response_store_path.display().to_string()),
+            ..(*ConfigurationProvider::default_config().unwrap()).clone()
+        })
+    }
+
+    #[tokio::test]
+    async fn should_build_independent_apps_from_config() {
+        let path = std::env::temp_dir().join(format!("orionznab-{}.redb", uuid::Uuid::new_v4()));
+        let app_config = app_config(&path);
+        let first = serve(AppState::from_config(app_config.clone()).unwrap()).await;
+        let second = serve(AppState::from_config(app_config).unwrap()).await;
+
+        for base in [first, second] {
+            let caps = reqwest::get(format!("{base}/api?t=caps")).await.unwrap();
+            assert_eq!(caps.status(), 200);
+            assert!(caps.text().await.unwrap().contains("<caps>"));
+        }
+
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn should_build_outside_a_runtime() {
+        let path = std::env::temp_dir().join(format!("orionznab-{}.redb", uuid::Uuid::new_v4()));
+        let state = AppState::from_config(app_config(&path)).unwrap();
+
+        drop(state);
+        let _ = std::fs::remove_file(path);
+    }
+}
@@ -0,0 +1,92 @@
+use std::net::SocketAddr;
+use std::path::PathBuf;
+
+const UNIX_PREFIX: &str = "unix:";
+
+/// An address the server accepts connections on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ListenAddress {
+    /// An IPv4 or IPv6 socket, e.g. `0.0.0.0:3000` or `[::]:3000`
+    Tcp(SocketAddr),
+    /// A Unix domain socket, given as `unix:/path/to/socket`
+    Unix(PathBuf),
+}
+
+impl std::fmt::Display for ListenAddress {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ListenAddress::Tcp(addr) => write!(f, "{addr}"),
+            ListenAddress::Unix(path) => write!(f, "{UNIX_PREFIX}{}", path.display()),
+        }
+    }
+}
+
+impl std::str::FromStr for ListenAddress {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if let Some(path) = s.strip_prefix(UNIX_PREFIX) {
+            if path.is_empty() {
+                anyhow::bail!("Unix socket listen address must include a path");
+            }
+            return Ok(ListenAddress::Unix(PathBuf::from(path)));
+        }
+
+        s.parse::<SocketAddr>()
+            .map(ListenAddress::Tcp)
+            .map_err(|e| anyhow::anyhow!("Invalid listen address '{s}': {e}"))
+    }
+}
+
+/// Parses a comma separated list of listen addresses.
+pub fn parse_listen_addresses(s: &str) -> anyhow::Result<Vec<ListenAddress>> {
+    let addresses = s
+        .split(',')
+        .filter(|address| !address.trim().is_empty())
+        .map(str::parse)
+        .collect::<anyhow::Result<Vec<ListenAddress>>>()?;
+
+    if addresses.is_empty() {
+        anyhow::bail!("At least one listen address is required");
+    }
+
+    Ok(addresses)
+}
+
+/// Normalizes a url base to `/segment` form, or an empty string when the app is served from the root.
+pub fn normalize_url_base(s: &str) -> String {
+    let trimmed = s.trim().trim_matches('/');
+    if trimmed.is_empty() {
+        String::new()
+    } else {
+        format!("/{trimmed}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_parse_listen_addresses() {
+        let addresses = parse_listen_addresses("0.0.0.0:3000, [::1]:3001,unix:/run/orionznab.sock").unwrap();
+
+        assert_eq!(addresses, vec![
+            ListenAddress::Tcp("0.0.0.0:3000".parse().unwrap()),
+            ListenAddress::Tcp("[::1]:3001".parse().unwrap()),
+            ListenAddress::Unix(PathBuf::from("/run/orionznab.sock")),
+        ]);
+        assert!(parse_listen_addresses("").is_err());
+        assert!(parse_listen_addresses("localhost").is_err());
+        assert!(parse_listen_addresses("unix:").is_err());
+    }
+
+    #[test]
+    fn should_normalize_url_base() {
+        assert_eq!(normalize_url_base(""), "");
+        assert_eq!(normalize_url_base("/"), "");
+        assert_eq!(normalize_url_base("orionznab/"), "/orionznab");
+        assert_eq!(normalize_url_base("/indexers/orionznab"), "/indexers/orionznab");
+    }
+}
@@ -1 +1,2 @@
-pub(crate) mod configuration_provider;
\ No newline at end of file
+pub mod configuration_provider;
+pub mod listen_address;
\ No newline at end of file
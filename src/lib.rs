@@ -0,0 +1,18 @@
+//! A Torznab api for [Orionoid](https://orionoid.com).
+//!
+//! The `orionznab` binary is a thin wrapper over this crate. To embed the indexer, build an [`AppState`]
+//! from an [`AppConfig`] and serve the router from [`torznab_router`], or use the [`OrionoidRequestClient`]
+//! and its [`SearchService`] on their own.
+mod utils;
+pub mod app_state;
+pub mod configuration;
+pub mod observability;
+pub mod request_clients;
+pub mod server;
+pub mod torznab;
+
+pub use app_state::AppState;
+pub use configuration::configuration_provider::{AppConfig, ConfigurationProvider};
+pub use request_clients::orionoid_client::orionoid_request_client::OrionoidRequestClient;
+pub use request_clients::orionoid_client::search_endpoints::SearchService;
+pub use torznab::torznab_router;
@@ -1,40 +1,116 @@
-mod utils;
-mod configuration;
-mod request_clients;
-mod torznab;
-
-use std::sync::Arc;
-use tracing::{debug};
-use tracing_subscriber::EnvFilter;
-use crate::configuration::configuration_provider::{AppConfig,ConfigurationProvider};
-use crate::request_clients::initialize_request_clients;
-use crate::torznab::initialize_torznab_api;
-
-fn init_tracing() {
+use std::time::Duration;
+use tokio::sync::watch;
+use tracing::{debug, info, warn};
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{EnvFilter, Layer};
+use orionznab::configuration::listen_address::parse_listen_addresses;
+use orionznab::observability::metrics_recorder::initialize_metrics;
+use orionznab::server::serve;
+use orionznab::{torznab_router, AppConfig, AppState, ConfigurationProvider};
+
+fn init_tracing(app_config: &AppConfig) {
     let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
-    tracing_subscriber::fmt().with_env_filter(filter).init();
+    let fmt_layer = match app_config.log_format.as_str() {
+        "json" => tracing_subscriber::fmt::layer().json().boxed(),
+        _ => tracing_subscriber::fmt::layer().boxed(),
+    };
+    let registry = tracing_subscriber::registry().with(filter).with(fmt_layer);
+
+    #[cfg(feature = "otel")]
+    let registry = registry.with(app_config.otlp_endpoint.as_deref().map(|endpoint| {
+        orionznab::observability::tracing_exporter::otlp_layer(endpoint).expect("Failed to initialize OpenTelemetry tracing")
+    }));
+
+    registry.init();
+
+    #[cfg(not(feature = "otel"))]
+    if app_config.otlp_endpoint.is_some() {
+        warn!("ORIONZNAB_OTLP_ENDPOINT is set, but orionznab was built without the `otel` feature");
+    }
 }
 
 #[tokio::main]
 async fn main() {
-    init_tracing();
-
     let app_config = ConfigurationProvider::load_config().expect("Failed to load configuration");
 
-    initialize_services(&app_config);
+    init_tracing(&app_config);
+    ConfigurationProvider::log_config(&app_config);
+
+    initialize_metrics().expect("Failed to initialize metrics");
+    let state = AppState::from_config(app_config.clone()).expect("Failed to initialize services");
+    state.orionoid_client.spawn_response_store_eviction(
+        Duration::from_secs(app_config.response_store_eviction_interval_seconds.max(1)),
+    );
+    debug!("Services initialized successfully");
+
+    let router = torznab_router(state.clone());
+
+    let listen_addresses = parse_listen_addresses(&app_config.listen)
+        .expect("Failed to parse listen addresses");
+
+    let (shutdown_tx, shutdown_rx) = watch::channel(false);
+    let servers = listen_addresses
+        .into_iter()
+        .map(|address| serve(address, router.clone(), wait_for_shutdown(shutdown_rx.clone())));
+    let servers = futures::future::try_join_all(servers);
+    tokio::pin!(servers);
 
-    let router = torznab::get_torznab_api();
+    tokio::select! {
+        result = &mut servers => {
+            result.expect("Server failed");
+        }
+        _ = shutdown_signal() => {
+            let drain_timeout = Duration::from_secs(app_config.shutdown_timeout_seconds);
+            info!("Shutting down, draining in-flight requests for up to {:?}", drain_timeout);
+            let _ = shutdown_tx.send(true);
 
-    let listener = tokio::net::TcpListener::bind("0.0.0.0:3000")
-        .await
-        .unwrap();
+            match tokio::time::timeout(drain_timeout, servers).await {
+                Ok(result) => {
+                    result.expect("Server failed");
+                    info!("In-flight requests drained");
+                }
+                Err(_) => warn!("Drain deadline elapsed, remaining requests were cut off"),
+            }
+        }
+    }
 
-    debug!("listening on {}", listener.local_addr().unwrap());
-    axum::serve(listener, router).await.unwrap();
+    flush_services(state).await;
 }
 
-fn initialize_services(app_config: &Arc<AppConfig>) {
-    initialize_request_clients(app_config.clone());
-    initialize_torznab_api(request_clients::get_orionoid_client().clone());
-    debug!("Services initialized successfully");
+/// Resolves once the process is asked to stop, with SIGINT (ctrl-c) or SIGTERM.
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        tokio::signal::ctrl_c().await.expect("Failed to listen for ctrl-c");
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+            .expect("Failed to listen for SIGTERM")
+            .recv()
+            .await;
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {},
+    }
+}
+
+/// Resolves once shutdown has been signalled to every listener.
+async fn wait_for_shutdown(mut shutdown_rx: watch::Receiver<bool>) {
+    let _ = shutdown_rx.wait_for(|shutdown| *shutdown).await;
+}
+
+async fn flush_services(state: AppState) {
+    state.orionoid_client.flush_response_store().await;
+
+    #[cfg(feature = "otel")]
+    orionznab::observability::tracing_exporter::shutdown_tracing();
+
+    info!("Shutdown complete");
 }
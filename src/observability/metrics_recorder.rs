@@ -0,0 +1,73 @@
+use std::time::Duration;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use metrics_exporter_prometheus::{Matcher, PrometheusBuilder, PrometheusHandle};
+use once_cell::sync::OnceCell;
+use tracing::debug;
+
+pub const TORZNAB_REQUESTS: &str = "orionznab_torznab_requests_total";
+pub const ORIONOID_REQUESTS: &str = "orionznab_orionoid_requests_total";
+pub const ORIONOID_REQUEST_DURATION: &str = "orionznab_orionoid_request_duration_seconds";
+pub const RATE_LIMITER_WAIT: &str = "orionznab_rate_limiter_wait_seconds";
+pub const SEARCH_RESULTS: &str = "orionznab_search_results";
+pub const DROPPED_ITEMS: &str = "orionznab_dropped_items_total";
+pub const SEARCH_CACHE_REQUESTS: &str = "orionznab_search_cache_requests_total";
+
+const DURATION_BUCKETS: &[f64] = &[0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0];
+const RESULT_BUCKETS: &[f64] = &[0.0, 1.0, 5.0, 10.0, 25.0, 50.0, 100.0, 250.0, 500.0, 1000.0];
+
+/// Histograms are only drained when upkeep runs, so it must happen regularly between scrapes.
+const UPKEEP_INTERVAL: Duration = Duration::from_secs(5);
+
+static METRICS_HANDLE: OnceCell<PrometheusHandle> = OnceCell::new();
+
+/// Installs the global Prometheus recorder. Until this is called every metric is a no-op.
+pub fn initialize_metrics() -> anyhow::Result<()> {
+    let handle = PrometheusBuilder::new()
+        .set_buckets_for_metric(Matcher::Suffix("_seconds".to_string()), DURATION_BUCKETS)?
+        .set_buckets_for_metric(Matcher::Full(SEARCH_RESULTS.to_string()), RESULT_BUCKETS)?
+        .install_recorder()?;
+
+    describe_metrics();
+
+    let upkeep_handle = handle.clone();
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(UPKEEP_INTERVAL);
+        loop {
+            ticker.tick().await;
+            upkeep_handle.run_upkeep();
+        }
+    });
+
+    METRICS_HANDLE
+        .set(handle)
+        .map_err(|_| anyhow::anyhow!("Metrics already initialized"))?;
+
+    debug!("Prometheus metrics recorder installed");
+    Ok(())
+}
+
+pub fn get_metrics_handle() -> Option<&'static PrometheusHandle> {
+    METRICS_HANDLE.get()
+}
+
+/// Renders every recorded metric in the Prometheus text exposition format.
+pub async fn render_metrics() -> Response {
+    match get_metrics_handle() {
+        Some(handle) => (
+            [("content-type", "text/plain; version=0.0.4")],
+            handle.render(),
+        ).into_response(),
+        None => (StatusCode::NOT_FOUND, "Metrics are not enabled").into_response(),
+    }
+}
+
+fn describe_metrics() {
+    metrics::describe_counter!(TORZNAB_REQUESTS, "Torznab api requests, by `t` type");
+    metrics::describe_counter!(ORIONOID_REQUESTS, "Requests sent to Orionoid, by response status");
+    metrics::describe_histogram!(ORIONOID_REQUEST_DURATION, metrics::Unit::Seconds, "Latency of requests sent to Orionoid");
+    metrics::describe_histogram!(RATE_LIMITER_WAIT, metrics::Unit::Seconds, "Time spent waiting on the Orionoid rate limiters");
+    metrics::describe_histogram!(SEARCH_RESULTS, "Results returned per search");
+    metrics::describe_counter!(DROPPED_ITEMS, "Orionoid streams dropped for missing a title, size or links");
+    metrics::describe_counter!(SEARCH_CACHE_REQUESTS, "Search cache lookups, by hit or miss");
+}
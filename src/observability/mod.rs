@@ -0,0 +1,3 @@
+pub mod metrics_recorder;
+#[cfg(feature = "otel")]
+pub mod tracing_exporter;
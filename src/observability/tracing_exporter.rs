@@ -0,0 +1,102 @@
+use anyhow::Context;
+use once_cell::sync::OnceCell;
+use opentelemetry::trace::TracerProvider;
+use opentelemetry_otlp::WithExportConfig;
+use opentelemetry_sdk::trace::SdkTracerProvider;
+use opentelemetry_sdk::Resource;
+use tracing::{warn, Subscriber};
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::Layer;
+
+const TRACES_PATH: &str = "/v1/traces";
+
+static TRACER_PROVIDER: OnceCell<SdkTracerProvider> = OnceCell::new();
+
+/// Resolves the traces url from a collector's base OTLP/HTTP endpoint, e.g. `http://localhost:4318`.
+fn traces_endpoint(endpoint: &str) -> String {
+    let endpoint = endpoint.trim().trim_end_matches('/');
+    if endpoint.ends_with(TRACES_PATH) {
+        endpoint.to_string()
+    } else {
+        format!("{endpoint}{TRACES_PATH}")
+    }
+}
+
+fn build_tracer_provider(endpoint: &str) -> anyhow::Result<SdkTracerProvider> {
+    let exporter = opentelemetry_otlp::SpanExporter::builder()
+        .with_http()
+        .with_endpoint(traces_endpoint(endpoint))
+        .build()
+        .context("Failed to build the OTLP span exporter")?;
+
+    let resource = Resource::builder()
+        .with_service_name(env!("CARGO_PKG_NAME"))
+        .build();
+
+    Ok(SdkTracerProvider::builder()
+        .with_batch_exporter(exporter)
+        .with_resource(resource)
+        .build())
+}
+
+/// Builds a layer exporting every span to the OTLP collector at `endpoint`.
+pub fn otlp_layer<S>(endpoint: &str) -> anyhow::Result<impl Layer<S>>
+where
+    S: Subscriber + for<'span> LookupSpan<'span>,
+{
+    let provider = build_tracer_provider(endpoint)?;
+    let tracer = provider.tracer(env!("CARGO_PKG_NAME"));
+
+    TRACER_PROVIDER
+        .set(provider)
+        .map_err(|_| anyhow::anyhow!("OpenTelemetry tracing already initialized"))?;
+
+    Ok(tracing_opentelemetry::layer().with_tracer(tracer))
+}
+
+/// Exports any spans still buffered, so they aren't lost when the process exits.
+pub fn shutdown_tracing() {
+    if let Some(provider) = TRACER_PROVIDER.get() {
+        if let Err(e) = provider.shutdown() {
+            warn!("Failed to flush OpenTelemetry spans: {e}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+    use axum::Router;
+    use axum::routing::post;
+    use opentelemetry::trace::{Tracer, TracerProvider};
+    use super::*;
+
+    #[test]
+    fn should_resolve_traces_endpoint() {
+        assert_eq!(traces_endpoint("http://localhost:4318"), "http://localhost:4318/v1/traces");
+        assert_eq!(traces_endpoint("http://localhost:4318/"), "http://localhost:4318/v1/traces");
+        assert_eq!(traces_endpoint("http://collector/v1/traces"), "http://collector/v1/traces");
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn should_export_spans_to_collector() {
+        let received = Arc::new(Mutex::new(Vec::<usize>::new()));
+        let collector = Router::new().route("/v1/traces", post({
+            let received = received.clone();
+            move |body: axum::body::Bytes| async move {
+                received.lock().unwrap().push(body.len());
+            }
+        }));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let endpoint = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, collector).await });
+
+        let provider = build_tracer_provider(&endpoint).unwrap();
+        provider.tracer("test").in_span("search", |_| {});
+        tokio::task::spawn_blocking(move || provider.force_flush()).await.unwrap().unwrap();
+
+        let received = received.lock().unwrap();
+        assert_eq!(received.len(), 1);
+        assert!(received[0] > 0);
+    }
+}
@@ -0,0 +1,209 @@
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use futures::future::BoxFuture;
+use serde::Serialize;
+use tracing::{info, warn};
+use crate::request_clients::rate_limited_client::Executor;
+use crate::request_clients::request_errors::error::Error;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CircuitState {
+    /// Requests flow through to Orionoid
+    Closed,
+    /// Orionoid is failing, so requests fail fast without being sent
+    Open,
+    /// A single probe request is allowed through to check whether Orionoid has recovered
+    HalfOpen,
+}
+
+#[derive(Debug)]
+enum BreakerState {
+    Closed { consecutive_failures: u32 },
+    Open { until: Instant },
+    HalfOpen { probe_in_flight: bool },
+}
+
+#[derive(Debug)]
+pub struct CircuitBreaker {
+    failure_threshold: u32,
+    open_duration: Duration,
+    state: Mutex<BreakerState>,
+}
+
+impl CircuitBreaker {
+    pub fn new(failure_threshold: u32, open_duration: Duration) -> Self {
+        Self {
+            failure_threshold: failure_threshold.max(1),
+            open_duration,
+            state: Mutex::new(BreakerState::Closed { consecutive_failures: 0 }),
+        }
+    }
+
+    pub fn state(&self) -> CircuitState {
+        match *self.state.lock().unwrap() {
+            BreakerState::Closed { .. } => CircuitState::Closed,
+            BreakerState::Open { until } if Instant::now() >= until => CircuitState::HalfOpen,
+            BreakerState::Open { .. } => CircuitState::Open,
+            BreakerState::HalfOpen { .. } => CircuitState::HalfOpen,
+        }
+    }
+
+    /// Whether a request may be sent, and if so whether it is the half-open probe.
+    fn try_acquire(&self) -> Option<bool> {
+        let mut state = self.state.lock().unwrap();
+        match *state {
+            BreakerState::Closed { .. } => Some(false),
+            BreakerState::Open { until } if Instant::now() >= until => {
+                info!("Orionoid circuit half-open, probing for recovery");
+                *state = BreakerState::HalfOpen { probe_in_flight: true };
+                Some(true)
+            }
+            BreakerState::Open { .. } => None,
+            BreakerState::HalfOpen { probe_in_flight: false } => {
+                *state = BreakerState::HalfOpen { probe_in_flight: true };
+                Some(true)
+            }
+            BreakerState::HalfOpen { probe_in_flight: true } => None,
+        }
+    }
+
+    fn record_success(&self) {
+        let mut state = self.state.lock().unwrap();
+        if !matches!(*state, BreakerState::Closed { .. }) {
+            info!("Orionoid circuit closed, requests resumed");
+        }
+        *state = BreakerState::Closed { consecutive_failures: 0 };
+    }
+
+    pub(crate) fn record_failure(&self) {
+        let mut state = self.state.lock().unwrap();
+        let consecutive_failures = match *state {
+            BreakerState::Closed { consecutive_failures } => consecutive_failures + 1,
+            _ => self.failure_threshold,
+        };
+
+        if consecutive_failures >= self.failure_threshold {
+            warn!(
+                "Orionoid circuit opened after {} consecutive failures, failing fast for {:?}",
+                consecutive_failures,
+                self.open_duration,
+            );
+            *state = BreakerState::Open { until: Instant::now() + self.open_duration };
+        } else {
+            *state = BreakerState::Closed { consecutive_failures };
+        }
+    }
+
+    /// A probe that never completed (e.g. the search was cancelled) lets the next request probe instead.
+    fn abandon_probe(&self) {
+        let mut state = self.state.lock().unwrap();
+        if let BreakerState::HalfOpen { probe_in_flight: true } = *state {
+            *state = BreakerState::HalfOpen { probe_in_flight: false };
+        }
+    }
+}
+
+struct ProbeGuard<'a> {
+    breaker: &'a CircuitBreaker,
+    armed: bool,
+}
+
+impl Drop for ProbeGuard<'_> {
+    fn drop(&mut self) {
+        if self.armed {
+            self.breaker.abandon_probe();
+        }
+    }
+}
+
+/// Stops sending requests through the [`Executor`] it wraps while Orionoid is failing.
+#[derive(Clone, Debug)]
+pub struct CircuitBreakerClient<E> {
+    inner: E,
+    breaker: Arc<CircuitBreaker>,
+}
+
+impl<E: Executor> CircuitBreakerClient<E> {
+    pub fn new(inner: E, breaker: Arc<CircuitBreaker>) -> Self {
+        Self { inner, breaker }
+    }
+
+    pub fn circuit_breaker(&self) -> &Arc<CircuitBreaker> {
+        &self.breaker
+    }
+
+    fn is_failure(result: &Result<reqwest::Response, Error>) -> bool {
+        match result {
+            Ok(response) => response.status().is_server_error(),
+            Err(Error::Request { .. }) | Err(Error::Response { .. }) | Err(Error::Reqwest(_)) => true,
+            Err(_) => false,
+        }
+    }
+}
+
+impl<E: Executor> Executor for CircuitBreakerClient<E> {
+    fn execute_raw(&self, url: &str) -> BoxFuture<'_, Result<reqwest::Response, Error>> {
+        let url = url.to_string();
+
+        Box::pin(async move {
+            let Some(is_probe) = self.breaker.try_acquire() else {
+                return Err(Error::ServiceUnavailable("Orionoid is currently unavailable".to_string()));
+            };
+
+            let mut guard = ProbeGuard { breaker: &self.breaker, armed: is_probe };
+            let result = self.inner.execute_raw(&url).await;
+            guard.armed = false;
+
+            if Self::is_failure(&result) {
+                self.breaker.record_failure();
+            } else {
+                self.breaker.record_success();
+            }
+
+            result
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_open_after_consecutive_failures() {
+        let breaker = CircuitBreaker::new(2, Duration::from_secs(60));
+
+        breaker.record_failure();
+        assert_eq!(breaker.state(), CircuitState::Closed);
+        breaker.record_failure();
+        assert_eq!(breaker.state(), CircuitState::Open);
+        assert_eq!(breaker.try_acquire(), None);
+    }
+
+    #[test]
+    fn should_reset_failures_on_success() {
+        let breaker = CircuitBreaker::new(2, Duration::from_secs(60));
+
+        breaker.record_failure();
+        breaker.record_success();
+        breaker.record_failure();
+        assert_eq!(breaker.state(), CircuitState::Closed);
+    }
+
+    #[test]
+    fn should_half_open_with_a_single_probe() {
+        let breaker = CircuitBreaker::new(1, Duration::ZERO);
+        breaker.record_failure();
+
+        assert_eq!(breaker.state(), CircuitState::HalfOpen);
+        assert_eq!(breaker.try_acquire(), Some(true));
+        assert_eq!(breaker.try_acquire(), None);
+
+        breaker.abandon_probe();
+        assert_eq!(breaker.try_acquire(), Some(true));
+
+        breaker.record_success();
+        assert_eq!(breaker.state(), CircuitState::Closed);
+    }
+}
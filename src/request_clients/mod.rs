@@ -1,36 +1,55 @@
 use std::sync::Arc;
-use once_cell::sync::OnceCell;
+use std::time::Duration;
+use anyhow::Context;
 use crate::configuration::configuration_provider::{AppConfig};
-use crate::request_clients::orionoid_client::orionoid_request_client;
-use crate::request_clients::orionoid_client::orionoid_request_client::OrionoidRequestClient;
+use crate::request_clients::orionoid_client::orionoid_request_client::{ClientBuilder, OrionoidExecutor, OrionoidRequestClient};
+use crate::request_clients::orionoid_client::quota_tracker::QuotaTracker;
+use crate::request_clients::orionoid_client::response_store::ResponseStore;
+use crate::request_clients::circuit_breaker_client::{CircuitBreaker, CircuitBreakerClient};
 use crate::request_clients::rate_limited_client::RateLimitedClient;
+use crate::request_clients::retrying_client::{RetryPolicy, RetryingClient};
+pub mod circuit_breaker_client;
 pub mod orionoid_client;
 pub mod rate_limited_client;
+pub mod retrying_client;
 pub mod request_errors;
 
-static ORIONOID_REQUEST_CLIENT: OnceCell<Arc<OrionoidRequestClient>> = OnceCell::new();
+/// Builds the Orionoid client described by the config, wrapped in rate limiting, retries and a circuit breaker.
+pub fn build_orionoid_request_client(app_config: &AppConfig) -> anyhow::Result<OrionoidRequestClient> {
+    let inner_client = orionoid_client_builder(app_config)?
+        .build()
+        .context("Failed to build Orionoid request client")?;
 
-pub fn initialize_request_clients(app_config: Arc<AppConfig>) {
-    initialize_orionoid_request_client(app_config.clone());
+    Ok(OrionoidRequestClient(inner_client))
 }
 
-pub fn initialize_orionoid_request_client(app_config: Arc<AppConfig>) {
-    let executor = RateLimitedClient::from_config(
-        &app_config.user_agent,
-        &app_config.orionoid_rate_limit,
-    ).expect("Failed to create Orionoid executor");
+/// A client builder configured from the config, for callers that need to adjust it before building.
+pub fn orionoid_client_builder(app_config: &AppConfig) -> anyhow::Result<ClientBuilder<OrionoidExecutor>> {
+    let rate_limited = RateLimitedClient::from_config(app_config)
+        .context("Failed to create Orionoid executor")?;
+    let retry_policy = RetryPolicy::from_config(app_config)
+        .context("Failed to create Orionoid retry policy")?;
+    let circuit_breaker = Arc::new(CircuitBreaker::new(
+        app_config.circuit_breaker_failure_threshold,
+        Duration::from_secs(app_config.circuit_breaker_open_seconds),
+    ));
+    let executor = CircuitBreakerClient::new(RetryingClient::new(rate_limited, retry_policy), circuit_breaker);
 
-    let inner_client = orionoid_request_client::ClientBuilder::default()
+    let mut builder = ClientBuilder::default()
+        .with_base_url(app_config.orionoid_base_url.clone())
+        .with_app_key(app_config.orionoid_app_key.clone())
         .with_executor(executor)
-        .build()
-        .expect("Failed to build Orionoid request client");
+        .with_quota_tracker(Arc::new(QuotaTracker::new(app_config.quota_warning_percent)));
+
+    if let Some(path) = app_config.response_store_path.as_deref().filter(|p| !p.trim().is_empty()) {
+        let store = ResponseStore::shared(
+            path,
+            Duration::from_secs(app_config.response_store_ttl_seconds),
+            app_config.response_store_max_size_mb * 1024 * 1024,
+        ).context("Failed to open Orionoid response store")?;
 
-    let client = OrionoidRequestClient(inner_client);
+        builder = builder.with_response_store(store);
+    }
 
-    ORIONOID_REQUEST_CLIENT
-        .set(Arc::new(client))
-        .expect("Orionoid request client already initialized");
+    Ok(builder)
 }
-pub fn get_orionoid_client() -> Arc<OrionoidRequestClient> {
-    ORIONOID_REQUEST_CLIENT.get().expect("Orionoid Client not initialized").clone()
-}
\ No newline at end of file
@@ -1,3 +1,7 @@
-pub(crate) mod orionoid_request_client;
-pub(crate) mod search_endpoints;
-pub(crate) mod types;
\ No newline at end of file
+pub mod orionoid_request_client;
+pub mod quota_tracker;
+pub mod response_store;
+pub mod search_cache;
+pub mod search_endpoints;
+pub mod stream_query;
+pub mod types;
\ No newline at end of file
@@ -1,43 +1,92 @@
 use std::borrow::Cow;
+use std::sync::Arc;
+use std::time::Duration;
 use anyhow::{anyhow, Result};
+use tracing::{debug, warn};
+use crate::request_clients::circuit_breaker_client::{CircuitBreakerClient, CircuitState};
+use crate::request_clients::orionoid_client::quota_tracker::QuotaTracker;
+use crate::request_clients::orionoid_client::response_store::ResponseStore;
 use crate::request_clients::orionoid_client::search_endpoints::SearchService;
+use crate::request_clients::orionoid_client::types::OrionApiResponse;
 use crate::request_clients::rate_limited_client::{Executor, RateLimitedClient};
+use crate::request_clients::retrying_client::RetryingClient;
 use crate::request_clients::request_errors::error::Error;
 
+/// Requests to Orionoid are rate limited, retried on transient failures, and stopped altogether while Orionoid is down.
+pub type OrionoidExecutor = CircuitBreakerClient<RetryingClient<RateLimitedClient>>;
+
 #[derive(Debug)]
 pub struct OrionoidRequestClient(
-    pub Client<RateLimitedClient>
+    pub Client<OrionoidExecutor>
 );
 
-const BASE_URL: &str = "https://api.orionoid.com";
+pub const DEFAULT_BASE_URL: &str = "https://api.orionoid.com";
+
+/// The app key orionznab is registered with at Orionoid
+pub const DEFAULT_APP_KEY: &str = "FGJKJFEBRHEMRFGSBGDLFPRGED96LJJL";
+
+/// Warn once fewer than this percentage of the daily Orionoid requests remain
+const DEFAULT_QUOTA_WARNING_PERCENT: u32 = 10;
 
 pub struct ClientBuilder<E: Executor> {
     base_url: Cow<'static, str>,
+    app_key: Cow<'static, str>,
     executor: Option<E>,
+    response_store: Option<Arc<ResponseStore>>,
+    quota_tracker: Option<Arc<QuotaTracker>>,
 }
 
 impl<E: Executor> Default for ClientBuilder<E> {
     fn default() -> Self {
         Self {
-            base_url: Cow::Borrowed(BASE_URL),
+            base_url: Cow::Borrowed(DEFAULT_BASE_URL),
+            app_key: Cow::Borrowed(DEFAULT_APP_KEY),
             executor: None,
+            response_store: None,
+            quota_tracker: None,
         }
     }
 }
 
 impl<E: Executor> ClientBuilder<E> {
+    /// Points the client at another Orionoid compatible api, e.g. a mirror or caching proxy.
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = Cow::Owned(base_url.into().trim_end_matches('/').to_string());
+        self
+    }
+
+    /// Identifies requests as coming from another app registered with Orionoid.
+    pub fn with_app_key(mut self, app_key: impl Into<String>) -> Self {
+        self.app_key = Cow::Owned(app_key.into());
+        self
+    }
+
     pub fn with_executor(mut self, executor: E) -> Self {
         self.executor = Some(executor);
         self
     }
 
+    pub fn with_response_store(mut self, response_store: Arc<ResponseStore>) -> Self {
+        self.response_store = Some(response_store);
+        self
+    }
+
+    pub fn with_quota_tracker(mut self, quota_tracker: Arc<QuotaTracker>) -> Self {
+        self.quota_tracker = Some(quota_tracker);
+        self
+    }
+
     pub fn build(self) -> Result<Client<E>> {
         let base_url = self.base_url;
         let executor = self.executor.ok_or_else(|| anyhow!("missing executor"))?;
 
         Ok(Client {
             executor,
-            base_url
+            base_url,
+            app_key: self.app_key,
+            response_store: self.response_store,
+            quota_tracker: self.quota_tracker
+                .unwrap_or_else(|| Arc::new(QuotaTracker::new(DEFAULT_QUOTA_WARNING_PERCENT))),
         })
     }
 }
@@ -45,6 +94,9 @@ impl<E: Executor> ClientBuilder<E> {
 pub struct Client<E> {
     executor: E,
     base_url: Cow<'static, str>,
+    app_key: Cow<'static, str>,
+    response_store: Option<Arc<ResponseStore>>,
+    quota_tracker: Arc<QuotaTracker>,
 }
 
 impl<E: std::fmt::Debug> std::fmt::Debug for Client<E> {
@@ -52,20 +104,74 @@ impl<E: std::fmt::Debug> std::fmt::Debug for Client<E> {
         f.debug_struct(stringify!(Client))
             .field("executor", &self.executor)
             .field("base_url", &self.base_url)
+            .field("app_key", &"<redacted>")
+            .field("response_store", &self.response_store)
+            .field("quota_tracker", &self.quota_tracker)
             .finish()
     }
 }
 
 impl OrionoidRequestClient {
-    pub async fn execute_raw<P>(&self, path: &str) -> Result<reqwest::Response, Error>
-    where
-        P: serde::Serialize + Send + Sync + 'static,
-    {
+    pub async fn execute_raw(&self, path: &str) -> Result<reqwest::Response, Error> {
         let url = format!("{}{}", self.0.base_url, path);
         self.0.executor
             .execute_raw(&url)
             .await
     }
 
+    /// Returns a previously stored response for the request path, when a persistent store is configured.
+    pub async fn stored_response(&self, path: &str) -> Option<OrionApiResponse> {
+        let store = self.0.response_store.clone()?;
+        let path = path.to_string();
+        let span = tracing::Span::current();
+        tokio::task::spawn_blocking(move || span.in_scope(|| store.get(&path))).await.ok().flatten()
+    }
+
+    pub async fn store_response(&self, path: &str, response: &OrionApiResponse) {
+        if let Some(store) = self.0.response_store.clone() {
+            let path = path.to_string();
+            let response = response.clone();
+            let span = tracing::Span::current();
+            let _ = tokio::task::spawn_blocking(move || span.in_scope(|| store.put(&path, &response))).await;
+        }
+    }
+
+    /// Starts evicting from the persisted store in the background, if the client has one.
+    pub fn spawn_response_store_eviction(&self, interval: Duration) -> Option<tokio::task::JoinHandle<()>> {
+        self.0.response_store.as_ref().map(|store| store.spawn_eviction(interval))
+    }
+
+    /// Runs a final eviction on shutdown, so the persisted store is left within its size cap.
+    pub async fn flush_response_store(&self) {
+        if let Some(store) = self.0.response_store.clone() {
+            match tokio::task::spawn_blocking(move || store.evict()).await {
+                Ok(Err(e)) => warn!("Failed to flush response store: {e}"),
+                Err(e) => warn!("Response store flush task failed: {e}"),
+                Ok(Ok(())) => debug!("Response store flushed"),
+            }
+        }
+    }
+
+    /// Checks Orionoid is reachable and not failing, without spending any user's quota.
+    pub async fn ping(&self) -> Result<(), Error> {
+        let response = self.execute_raw("/").await?;
+        if response.status().is_server_error() {
+            return Err(Error::ServiceUnavailable(format!("Orionoid responded with {}", response.status())));
+        }
+        Ok(())
+    }
+
+    pub fn circuit_state(&self) -> CircuitState {
+        self.0.executor.circuit_breaker().state()
+    }
+
+    pub fn quota_tracker(&self) -> &Arc<QuotaTracker> {
+        &self.0.quota_tracker
+    }
+
+    pub fn app_key(&self) -> &str {
+        &self.0.app_key
+    }
+
     pub fn search_endpoints(&self) -> SearchService<'_> { SearchService { client: self } }
 }
\ No newline at end of file
@@ -0,0 +1,171 @@
+use std::collections::HashMap;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::sync::Mutex;
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use tracing::{info, warn};
+use crate::request_clients::orionoid_client::types::OrionRequests;
+
+/// The daily Orionoid request quota last reported for an API key.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct QuotaUsage {
+    /// A short digest identifying the API key, never the key itself
+    pub key: String,
+    pub limit: Option<u32>,
+    pub used: Option<u32>,
+    pub remaining: Option<u32>,
+    pub total: Option<u32>,
+    pub updated: DateTime<Utc>,
+}
+
+impl QuotaUsage {
+    fn is_current(&self, today: NaiveDate) -> bool {
+        self.updated.date_naive() == today
+    }
+
+    fn is_exhausted(&self) -> bool {
+        self.remaining == Some(0)
+    }
+
+    fn is_low(&self, warning_percent: u32) -> bool {
+        match (self.remaining, self.limit) {
+            (Some(remaining), Some(limit)) if limit > 0 => remaining.saturating_mul(100) <= limit.saturating_mul(warning_percent),
+            _ => false,
+        }
+    }
+}
+
+/// Tracks the daily quota Orionoid reports back on every response, per API key.
+#[derive(Debug)]
+pub struct QuotaTracker {
+    usage: Mutex<HashMap<u64, QuotaUsage>>,
+    /// Remaining requests, as a percentage of the daily limit, below which warnings are logged
+    warning_percent: u32,
+}
+
+impl QuotaTracker {
+    pub fn new(warning_percent: u32) -> Self {
+        Self {
+            usage: Mutex::new(HashMap::new()),
+            warning_percent,
+        }
+    }
+
+    pub fn record(&self, api_token: &str, requests: &OrionRequests) {
+        let Some(daily) = requests.daily.as_ref() else {
+            return;
+        };
+
+        let hash = Self::hash(api_token);
+        let usage = QuotaUsage {
+            key: Self::digest(api_token),
+            limit: daily.limit,
+            used: daily.used,
+            remaining: daily.remaining,
+            total: requests.total,
+            updated: Utc::now(),
+        };
+
+        let mut tracked = self.usage.lock().unwrap();
+        let was_low = tracked.get(&hash)
+            .filter(|previous| previous.is_current(usage.updated.date_naive()))
+            .is_some_and(|previous| previous.is_low(self.warning_percent));
+
+        if usage.is_exhausted() {
+            warn!("Orionoid daily request limit reached for key {}", usage.key);
+        } else if usage.is_low(self.warning_percent) && !was_low {
+            warn!(
+                "Orionoid daily requests running low for key {}: {} of {} remaining",
+                usage.key,
+                usage.remaining.unwrap_or_default(),
+                usage.limit.unwrap_or_default(),
+            );
+        } else if was_low && !usage.is_low(self.warning_percent) {
+            info!("Orionoid daily requests replenished for key {}", usage.key);
+        }
+
+        tracked.insert(hash, usage);
+    }
+
+    /// Records that Orionoid rejected the key for reaching its daily limit, which it reports without any usage.
+    pub fn mark_exhausted(&self, api_token: &str) {
+        let now = Utc::now();
+        let mut tracked = self.usage.lock().unwrap();
+        let previous = tracked.get(&Self::hash(api_token)).filter(|usage| usage.is_current(now.date_naive()));
+
+        let usage = QuotaUsage {
+            key: Self::digest(api_token),
+            limit: previous.and_then(|usage| usage.limit),
+            used: previous.and_then(|usage| usage.limit.or(usage.used)),
+            remaining: Some(0),
+            total: previous.and_then(|usage| usage.total),
+            updated: now,
+        };
+
+        warn!("Orionoid daily request limit reached for key {}", usage.key);
+        tracked.insert(Self::hash(api_token), usage);
+    }
+
+    /// Whether the key has used up today's quota, so further requests would only be rejected by Orionoid.
+    pub fn is_exhausted(&self, api_token: &str) -> bool {
+        let tracked = self.usage.lock().unwrap();
+        tracked.get(&Self::hash(api_token))
+            .is_some_and(|usage| usage.is_current(Utc::now().date_naive()) && usage.is_exhausted())
+    }
+
+    pub fn usage(&self, api_token: &str) -> Option<QuotaUsage> {
+        self.usage.lock().unwrap().get(&Self::hash(api_token)).cloned()
+    }
+
+    /// A short, stable digest of the key that can be shown back to its owner without revealing it.
+    fn digest(api_token: &str) -> String {
+        let digest = Sha256::digest(api_token.as_bytes());
+        digest[..4].iter().map(|byte| format!("{byte:02x}")).collect()
+    }
+
+    fn hash(api_token: &str) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        api_token.hash(&mut hasher);
+        hasher.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn requests(limit: u32, used: u32) -> OrionRequests {
+        serde_json::from_value(serde_json::json!({
+            "total": used,
+            "daily": { "limit": limit, "used": used, "remaining": limit - used }
+        })).unwrap()
+    }
+
+    #[test]
+    fn should_track_usage_per_key() {
+        let tracker = QuotaTracker::new(10);
+        tracker.record("first", &requests(100, 40));
+        tracker.record("second", &requests(100, 100));
+
+        assert_eq!(tracker.usage("first").unwrap().remaining, Some(60));
+        assert!(!tracker.is_exhausted("first"));
+        assert!(tracker.is_exhausted("second"));
+        assert!(!tracker.is_exhausted("unknown"));
+        assert_eq!(tracker.usage("second").unwrap().used, Some(100));
+    }
+
+    #[test]
+    fn should_mark_keys_exhausted_for_the_day() {
+        let tracker = QuotaTracker::new(10);
+        tracker.mark_exhausted("unknown");
+        tracker.record("known", &requests(100, 40));
+        tracker.mark_exhausted("known");
+
+        assert!(tracker.is_exhausted("unknown"));
+        assert_eq!(tracker.usage("unknown").unwrap().limit, None);
+        assert!(tracker.is_exhausted("known"));
+        assert_eq!(tracker.usage("known").unwrap().limit, Some(100));
+        assert_eq!(tracker.usage("known").unwrap().used, Some(100));
+    }
+}
@@ -0,0 +1,291 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, RwLock, Weak};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use once_cell::sync::Lazy;
+use redb::{Database, ReadableTable, TableDefinition};
+use sha2::{Digest, Sha256};
+use tracing::{debug, info, warn};
+use crate::request_clients::orionoid_client::types::OrionApiResponse;
+
+/// Values are the big-endian expiry timestamp (unix seconds) followed by the JSON encoded response.
+const RESPONSES: TableDefinition<&str, &[u8]> = TableDefinition::new("orionoid_responses");
+const EXPIRY_LEN: usize = size_of::<u64>();
+
+/// redb locks its file, so every store still in use is kept here to be handed out again for the same path.
+static OPEN_STORES: Lazy<Mutex<HashMap<PathBuf, Weak<ResponseStore>>>> = Lazy::new(Default::default);
+
+/// A persistent, single-file store of Orionoid responses, so cached lookups survive restarts.
+pub struct ResponseStore {
+    /// Compacting needs the database to itself, so every transaction holds a read lock while it runs
+    database: RwLock<Database>,
+    path: PathBuf,
+    ttl: Duration,
+    max_size_bytes: u64,
+}
+
+impl std::fmt::Debug for ResponseStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct(stringify!(ResponseStore))
+            .field("ttl", &self.ttl)
+            .field("max_size_bytes", &self.max_size_bytes)
+            .finish()
+    }
+}
+
+impl ResponseStore {
+    pub fn open(path: impl AsRef<Path>, ttl: Duration, max_size_bytes: u64) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let database = Database::create(path)
+            .map_err(|e| anyhow::anyhow!("Failed to open response store at {}: {e}", path.display()))?;
+
+        // Make sure the table exists so read transactions never fail on a fresh database
+        let txn = database.begin_write()?;
+        txn.open_table(RESPONSES)?;
+        txn.commit()?;
+
+        info!("Opened persistent response store at {}", path.display());
+
+        Ok(Self { database: RwLock::new(database), path: path.to_path_buf(), ttl, max_size_bytes })
+    }
+
+    /// Opens the store at a path, or shares the one already open there as long as it was opened with the same settings.
+    pub fn shared(path: impl AsRef<Path>, ttl: Duration, max_size_bytes: u64) -> anyhow::Result<Arc<Self>> {
+        let path = path.as_ref().to_path_buf();
+        let mut open_stores = OPEN_STORES.lock().unwrap_or_else(|e| e.into_inner());
+        open_stores.retain(|_, store| store.strong_count() > 0);
+
+        if let Some(store) = open_stores.get(&path).and_then(Weak::upgrade) {
+            if store.ttl != ttl || store.max_size_bytes != max_size_bytes {
+                return Err(anyhow::anyhow!(
+                    "Response store at {} is already open with a ttl of {:?} and a max size of {} bytes",
+                    path.display(),
+                    store.ttl,
+                    store.max_size_bytes,
+                ));
+            }
+            return Ok(store);
+        }
+
+        let store = Arc::new(Self::open(&path, ttl, max_size_bytes)?);
+        open_stores.insert(path, Arc::downgrade(&store));
+        Ok(store)
+    }
+
+    /// Returns the stored response for a request path, if present and not yet expired.
+    pub fn get(&self, path: &str) -> Option<OrionApiResponse> {
+        let key = Self::key_for(path);
+        let lookup = || -> anyhow::Result<Option<OrionApiResponse>> {
+            let txn = self.database.read().unwrap_or_else(|e| e.into_inner()).begin_read()?;
+            let table = txn.open_table(RESPONSES)?;
+            let Some(value) = table.get(key.as_str())? else {
+                return Ok(None);
+            };
+
+            let value = value.value();
+            if value.len() < EXPIRY_LEN || Self::expires_at(value) <= now() {
+                return Ok(None);
+            }
+
+            Ok(Some(serde_json::from_slice(&value[EXPIRY_LEN..])?))
+        };
+
+        match lookup() {
+            Ok(response) => {
+                debug!("Response store {} for request", if response.is_some() { "hit" } else { "miss" });
+                response
+            }
+            Err(e) => {
+                warn!("Failed to read from response store: {e}");
+                None
+            }
+        }
+    }
+
+    pub fn put(&self, path: &str, response: &OrionApiResponse) {
+        let key = Self::key_for(path);
+        let store = || -> anyhow::Result<()> {
+            let expires_at = now() + self.ttl.as_secs();
+            let mut value = expires_at.to_be_bytes().to_vec();
+            serde_json::to_writer(&mut value, response)?;
+
+            let txn = self.database.read().unwrap_or_else(|e| e.into_inner()).begin_write()?;
+            {
+                let mut table = txn.open_table(RESPONSES)?;
+                table.insert(key.as_str(), value.as_slice())?;
+            }
+            txn.commit()?;
+            Ok(())
+        };
+
+        if let Err(e) = store() {
+            warn!("Failed to write to response store: {e}");
+        }
+    }
+
+    /// Removes expired responses, then the soonest to expire until the store fits within its maximum size. redb
+    /// reuses freed space but never gives it back, so the file is compacted once it grows past the maximum size.
+    pub fn evict(&self) -> anyhow::Result<()> {
+        let now = now();
+        let database = self.database.read().unwrap_or_else(|e| e.into_inner());
+        let txn = database.begin_write()?;
+        let removed = {
+            let mut table = txn.open_table(RESPONSES)?;
+            let mut removed = 0usize;
+
+            let mut entries = Vec::new();
+            for entry in table.iter()? {
+                let (key, value) = entry?;
+                let value = value.value();
+                let expires_at = if value.len() < EXPIRY_LEN { 0 } else { Self::expires_at(value) };
+                entries.push((key.value().to_string(), expires_at, value.len() as u64));
+            }
+
+            entries.sort_by_key(|(_, expires_at, _)| *expires_at);
+            let mut total_size: u64 = entries.iter().map(|(_, _, size)| size).sum();
+
+            for (key, expires_at, size) in entries {
+                if expires_at > now && total_size <= self.max_size_bytes {
+                    break;
+                }
+                table.remove(key.as_str())?;
+                total_size -= size;
+                removed += 1;
+            }
+            removed
+        };
+        txn.commit()?;
+        drop(database);
+
+        if removed > 0 {
+            debug!("Evicted {removed} responses from the response store");
+        }
+
+        if removed > 0 && std::fs::metadata(&self.path)?.len() > self.max_size_bytes {
+            let mut database = self.database.write().unwrap_or_else(|e| e.into_inner());
+            database.compact()?;
+            debug!("Compacted the response store to {} bytes", std::fs::metadata(&self.path)?.len());
+        }
+        Ok(())
+    }
+
+    /// Periodically evicts from the store on a background task, until the store is dropped.
+    pub fn spawn_eviction(self: &Arc<Self>, interval: Duration) -> tokio::task::JoinHandle<()> {
+        let store = Arc::downgrade(self);
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                let Some(store) = store.upgrade() else {
+                    break;
+                };
+                match tokio::task::spawn_blocking(move || store.evict()).await {
+                    Ok(Err(e)) => warn!("Failed to evict from response store: {e}"),
+                    Err(e) => warn!("Response store eviction task failed: {e}"),
+                    Ok(Ok(())) => {}
+                }
+            }
+        })
+    }
+
+    /// Request paths carry the user's API key, so only a digest of them is persisted.
+    fn key_for(path: &str) -> String {
+        Sha256::digest(path.as_bytes())
+            .iter()
+            .map(|b| format!("{b:02x}"))
+            .collect()
+    }
+
+    fn expires_at(value: &[u8]) -> u64 {
+        let mut expiry = [0u8; EXPIRY_LEN];
+        expiry.copy_from_slice(&value[..EXPIRY_LEN]);
+        u64::from_be_bytes(expiry)
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_store(ttl: Duration, max_size_bytes: u64) -> (ResponseStore, std::path::PathBuf) {
+        let path = std::env::temp_dir().join(format!("orionznab-{}.redb", uuid::Uuid::new_v4()));
+        (ResponseStore::open(&path, ttl, max_size_bytes).unwrap(), path)
+    }
+
+    fn response(name: &str) -> OrionApiResponse {
+        serde_json::from_value(serde_json::json!({ "name": name })).unwrap()
+    }
+
+    #[test]
+    fn should_share_stores_opened_at_the_same_path() {
+        let path = std::env::temp_dir().join(format!("orionznab-{}.redb", uuid::Uuid::new_v4()));
+        let first = ResponseStore::shared(&path, Duration::from_secs(60), u64::MAX).unwrap();
+        let second = ResponseStore::shared(&path, Duration::from_secs(60), u64::MAX).unwrap();
+        assert!(Arc::ptr_eq(&first, &second));
+        assert!(ResponseStore::shared(&path, Duration::from_secs(30), u64::MAX).is_err());
+        assert!(ResponseStore::shared(&path, Duration::from_secs(60), 1024).is_err());
+
+        drop((first, second));
+        let reopened = ResponseStore::shared(&path, Duration::from_secs(60), u64::MAX).unwrap();
+        reopened.put("/?query=alien", &response("alien"));
+
+        drop(reopened);
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn should_round_trip_responses() {
+        let (store, path) = temp_store(Duration::from_secs(60), u64::MAX);
+
+        assert!(store.get("/?query=alien").is_none());
+        store.put("/?query=alien", &response("alien"));
+        assert_eq!(store.get("/?query=alien").unwrap().name.as_deref(), Some("alien"));
+
+        drop(store);
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn should_evict_expired_and_oversized_responses() {
+        let (store, path) = temp_store(Duration::ZERO, u64::MAX);
+        store.put("/?query=alien", &response("alien"));
+        assert!(store.get("/?query=alien").is_none());
+        store.evict().unwrap();
+
+        let store = ResponseStore { ttl: Duration::from_secs(60), max_size_bytes: 0, ..store };
+        store.put("/?query=aliens", &response("aliens"));
+        store.evict().unwrap();
+        assert!(store.get("/?query=aliens").is_none());
+
+        drop(store);
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn should_shrink_the_file_after_evicting() {
+        let (store, path) = temp_store(Duration::from_secs(60), u64::MAX);
+        let name = "x".repeat(64 * 1024);
+        for index in 0..64 {
+            store.put(&format!("/?query={index}"), &response(&name));
+        }
+        let grown = std::fs::metadata(&path).unwrap().len();
+
+        let store = ResponseStore { max_size_bytes: 0, ..store };
+        store.evict().unwrap();
+
+        assert!(std::fs::metadata(&path).unwrap().len() < grown);
+        drop(store);
+        let _ = std::fs::remove_file(path);
+    }
+}
@@ -0,0 +1,202 @@
+use std::future::Future;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::time::Duration;
+use moka::future::Cache;
+use tracing::{debug};
+use crate::observability::metrics_recorder::SEARCH_CACHE_REQUESTS;
+use crate::request_clients::orionoid_client::search_endpoints::{MediaIds, PageRequest};
+use crate::request_clients::orionoid_client::types::OrionApiResponse;
+use crate::request_clients::request_errors::error::Error;
+
+/// Identifies a single Orionoid lookup. The API key is only ever kept as a hash.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SearchCacheKey {
+    apikey_hash: u64,
+    media_type: &'static str,
+    query: Option<String>,
+    imdb: Option<String>,
+    tmdb: Option<String>,
+    tvdb: Option<String>,
+    trakt: Option<String>,
+    tvrage: Option<String>,
+    season: Option<u32>,
+    episode: Option<u32>,
+    offset: u32,
+    limit: u32,
+}
+
+impl SearchCacheKey {
+    pub fn new(
+        api_token: &str,
+        media_type: &'static str,
+        query: Option<&str>,
+        ids: MediaIds<'_>,
+        season: Option<u32>,
+        episode: Option<u32>,
+        page: PageRequest,
+    ) -> Self {
+        let mut hasher = DefaultHasher::new();
+        api_token.hash(&mut hasher);
+
+        let normalize = |value: Option<&str>| {
+            value
+                .map(|v| v.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase())
+                .filter(|v| !v.is_empty())
+        };
+
+        Self {
+            apikey_hash: hasher.finish(),
+            media_type,
+            query: normalize(query),
+            imdb: normalize(ids.imdb),
+            tmdb: normalize(ids.tmdb),
+            tvdb: normalize(ids.tvdb),
+            trakt: normalize(ids.trakt),
+            tvrage: normalize(ids.tvrage),
+            season,
+            episode,
+            offset: page.offset,
+            limit: page.limit,
+        }
+    }
+
+    /// Lookups without a query or ids list the latest releases, which change constantly.
+    fn has_search_terms(&self) -> bool {
+        [&self.query, &self.imdb, &self.tmdb, &self.tvdb, &self.trakt, &self.tvrage]
+            .iter()
+            .any(|term| term.is_some())
+    }
+}
+
+/// A TTL bounded in-memory cache of Orionoid responses, so repeated lookups don't burn the daily quota.
+#[derive(Debug, Clone)]
+pub struct SearchCache {
+    inner: Option<Cache<SearchCacheKey, OrionApiResponse>>,
+}
+
+impl SearchCache {
+    /// Creates a cache holding up to `max_entries` responses for `ttl`. A zero for either disables caching.
+    pub fn new(ttl: Duration, max_entries: u64) -> Self {
+        if ttl.is_zero() || max_entries == 0 {
+            return Self::disabled();
+        }
+
+        let inner = Cache::builder()
+            .max_capacity(max_entries)
+            .time_to_live(ttl)
+            .build();
+
+        Self { inner: Some(inner) }
+    }
+
+    pub fn disabled() -> Self {
+        Self { inner: None }
+    }
+
+    pub async fn get_or_fetch<F>(&self, key: SearchCacheKey, fetch: F) -> Result<OrionApiResponse, Error>
+    where
+        F: Future<Output = Result<OrionApiResponse, Error>>,
+    {
+        // The latest releases are never cached, so every RSS poll sees new ones
+        let Some(cache) = self.inner.as_ref().filter(|_| key.has_search_terms()) else {
+            return fetch.await;
+        };
+
+        if let Some(response) = cache.get(&key).await {
+            metrics::counter!(SEARCH_CACHE_REQUESTS, "result" => "hit").increment(1);
+            debug!("Search cache hit for {} lookup", key.media_type);
+            return Ok(response);
+        }
+
+        metrics::counter!(SEARCH_CACHE_REQUESTS, "result" => "miss").increment(1);
+        debug!("Search cache miss for {} lookup", key.media_type);
+        let response = fetch.await?;
+        if response.is_success() {
+            cache.insert(key, response.clone()).await;
+        }
+        Ok(response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(api_token: &str, query: &str) -> SearchCacheKey {
+        SearchCacheKey::new(
+            api_token,
+            "movie",
+            Some(query),
+            MediaIds::default(),
+            None,
+            None,
+            PageRequest { offset: 0, limit: 50 },
+        )
+    }
+
+    fn response(name: &str) -> OrionApiResponse {
+        serde_json::from_value(serde_json::json!({ "name": name, "result": { "status": "success" } })).unwrap()
+    }
+
+    #[test]
+    fn should_normalize_queries() {
+        assert_eq!(key("token", "The  Matrix "), key("token", "the matrix"));
+        assert_ne!(key("token", "the matrix"), key("other", "the matrix"));
+    }
+
+    #[tokio::test]
+    async fn should_serve_cached_response() {
+        let cache = SearchCache::new(Duration::from_secs(60), 10);
+
+        let first = cache.get_or_fetch(key("token", "alien"), async { Ok(response("first")) }).await.unwrap();
+        let second = cache.get_or_fetch(key("token", "alien"), async { Ok(response("second")) }).await.unwrap();
+
+        assert_eq!(first.name.as_deref(), Some("first"));
+        assert_eq!(second.name.as_deref(), Some("first"));
+    }
+
+    #[tokio::test]
+    async fn should_not_cache_latest_releases() {
+        let cache = SearchCache::new(Duration::from_secs(60), 10);
+        let latest = || SearchCacheKey::new(
+            "token",
+            "movie",
+            Some(" "),
+            MediaIds::default(),
+            None,
+            None,
+            PageRequest { offset: 0, limit: 50 },
+        );
+
+        cache.get_or_fetch(latest(), async { Ok(response("first")) }).await.unwrap();
+        let second = cache.get_or_fetch(latest(), async { Ok(response("second")) }).await.unwrap();
+
+        assert_eq!(second.name.as_deref(), Some("second"));
+    }
+
+    #[tokio::test]
+    async fn should_not_cache_errors() {
+        let cache = SearchCache::new(Duration::from_secs(60), 10);
+
+        let failed = cache.get_or_fetch(key("token", "alien"), async { Err(Error::Custom("boom".to_string())) }).await;
+        let fetched = cache.get_or_fetch(key("token", "alien"), async { Ok(response("fetched")) }).await.unwrap();
+
+        assert!(failed.is_err());
+        assert_eq!(fetched.name.as_deref(), Some("fetched"));
+    }
+
+    #[tokio::test]
+    async fn should_not_cache_unsuccessful_responses() {
+        let cache = SearchCache::new(Duration::from_secs(60), 10);
+        let unsuccessful: OrionApiResponse = serde_json::from_value(serde_json::json!({
+            "name": "unsuccessful",
+            "result": { "status": "error", "type": "userlimit" }
+        })).unwrap();
+
+        let first = cache.get_or_fetch(key("token", "alien"), async { Ok(unsuccessful) }).await.unwrap();
+        let second = cache.get_or_fetch(key("token", "alien"), async { Ok(response("fetched")) }).await.unwrap();
+
+        assert_eq!(first.name.as_deref(), Some("unsuccessful"));
+        assert_eq!(second.name.as_deref(), Some("fetched"));
+    }
+}
@@ -1,105 +1,364 @@
+use reqwest::StatusCode;
+use serde::Deserialize;
 use crate::request_clients::request_errors::error::Error;
+use crate::request_clients::request_errors::server_other_body_error::ServerOtherBodyError;
+use crate::request_clients::request_errors::server_validation_error::ServerValidationBodyError;
 use crate::request_clients::orionoid_client::orionoid_request_client::{OrionoidRequestClient};
-use crate::request_clients::orionoid_client::types::{OrionApiResponse};
+use crate::request_clients::orionoid_client::stream_query::{OrionMediaType, OrionSortOrder, OrionSortValue, OrionStreamQuery};
+use crate::request_clients::orionoid_client::types::{OrionApiResponse, OrionResult};
 use tracing::{debug};
 
-const DEFAULT_MOVIE_QUERY: &str = "the matrix";
-const DEFAULT_TV_QUERY: &str = "the flash";
+/// The window of results requested by a Torznab client.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PageRequest {
+    pub offset: u32,
+    pub limit: u32,
+}
+
+/// External identifiers Orionoid can look media up by.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MediaIds<'a> {
+    pub imdb: Option<&'a str>,
+    pub tmdb: Option<&'a str>,
+    pub tvdb: Option<&'a str>,
+    pub trakt: Option<&'a str>,
+    pub tvrage: Option<&'a str>,
+}
+
+impl MediaIds<'_> {
+    fn is_empty(&self) -> bool {
+        self.imdb.is_none()
+            && self.tmdb.is_none()
+            && self.tvdb.is_none()
+            && self.trakt.is_none()
+            && self.tvrage.is_none()
+    }
+}
+
+/// Orionoid pages results with `limitcount`/`limitpage`, while Torznab asks for an arbitrary item offset.
+/// An offset that isn't page aligned is covered by the page it falls in and the one after it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Paging {
+    count: u32,
+    page: u32,
+    /// Streams to drop from the front of the retrieved pages to honour an offset that isn't page aligned
+    skip: u32,
+}
+
+impl Paging {
+    fn from_page_request(page: PageRequest) -> Self {
+        let PageRequest { offset, limit } = page;
+        let limit = limit.max(1);
+        Self { count: limit, page: (offset / limit).saturating_add(1), skip: offset % limit }
+    }
+}
+
+/// Just enough of a response to tell whether Orionoid reported an error, without assuming the rest of its shape.
+#[derive(Deserialize)]
+struct OrionResultEnvelope {
+    result: Option<OrionResult>,
+}
+
+/// Maps an Orionoid error result to an [`Error`] by its `type`, e.g. `userkey`, `usersuspended`, `userlimit`, `appkey`,
+/// `maintenance` or `medianotfound`.
+fn orionoid_error(status: StatusCode, result: &OrionResult) -> Error {
+    let error_type = result.result_type.as_deref().unwrap_or_default().to_lowercase();
+    let description = result.description.clone()
+        .or_else(|| result.message.clone())
+        .unwrap_or_else(|| format!("Orionoid returned a '{error_type}' error"));
+
+    if error_type.contains("limit") || error_type.contains("exhausted") {
+        Error::RequestLimitReached(description)
+    } else if error_type.contains("suspended") || error_type.contains("banned") {
+        Error::AccountSuspended(description)
+    } else if error_type.starts_with("user") {
+        Error::Unauthorized(description)
+    } else if error_type.contains("maintenance") || error_type.contains("unavailable") {
+        Error::ServiceUnavailable(description)
+    } else if error_type.starts_with("app") || status.is_server_error() {
+        // A bad app key is a problem with this service's configuration, not the search
+        server_error(status, description)
+    } else if error_type.contains("notfound") || status == StatusCode::NOT_FOUND {
+        Error::NotFound(description)
+    } else {
+        Error::Validation(ServerValidationBodyError { errors: vec![description] })
+    }
+}
+
+/// Maps an error status that came without an Orionoid error result.
+fn status_error(status: StatusCode) -> Error {
+    let message = format!("Orionoid responded with {status}");
+    match status {
+        StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => Error::Unauthorized(message),
+        StatusCode::NOT_FOUND => Error::NotFound(message),
+        StatusCode::TOO_MANY_REQUESTS => Error::RequestLimitReached(message),
+        StatusCode::SERVICE_UNAVAILABLE => Error::ServiceUnavailable(message),
+        status if status.is_client_error() => Error::Validation(ServerValidationBodyError { errors: vec![message] }),
+        status => server_error(status, message),
+    }
+}
+
+fn server_error(status: StatusCode, status_message: String) -> Error {
+    Error::Server {
+        code: status.as_u16(),
+        content: ServerOtherBodyError { status_code: status.as_u16(), status_message },
+    }
+}
 
 pub struct SearchService<'a> {
     pub(crate) client: &'a OrionoidRequestClient,
 }
 
 impl<'a> SearchService<'a> {
+    #[tracing::instrument(skip_all, fields(query = query.unwrap_or_default(), ?ids, offset = page.offset, limit = page.limit))]
     pub async fn search_movie(
         &self,
         api_token: &str,
         query: Option<&str>,
-        imdbid: Option<&str>,
-        max_results: u32,
+        ids: MediaIds<'_>,
+        page: PageRequest,
     ) -> Result<OrionApiResponse, Error> {
-        if api_token.is_empty() {
-            return Err(Error::Custom("API key is required".to_string()));
-        }
-        
-        let final_query = Some(query.unwrap_or(DEFAULT_MOVIE_QUERY).to_lowercase().to_string());
-        
-        let mut url = format!(
-            "/?keyapp=FGJKJFEBRHEMRFGSBGDLFPRGED96LJJL&keyuser={api_token}&streamtype=torrent&mode=stream&action=retrieve&type=movie&sortvalue=videoquality&sortorder=ascending",
-            api_token = api_token,
-        );
+        let mut stream_query = OrionStreamQuery {
+            sort_value: Some(OrionSortValue::VideoQuality),
+            sort_order: Some(OrionSortOrder::Ascending),
+            ..OrionStreamQuery::new(OrionMediaType::Movie)
+        };
 
-        if imdbid.is_some() {
-            self.append_query_param(&mut url, "idimdb", &imdbid);
-        } else {
-            self.append_query_param(&mut url, "query", &final_query);
+        // Orionoid has no TVDB or TVRage ids for movies, so a lookup by only those can't match any
+        let movie_ids = MediaIds { tvdb: None, tvrage: None, ..ids };
+        if movie_ids.is_empty() && !ids.is_empty() && Self::search_query(query).is_none() {
+            return Ok(OrionApiResponse::empty());
         }
+        Self::apply_search_terms(&mut stream_query, query, &movie_ids);
 
-        url.push_str(&format!("&limitcount={}", max_results));
-
-        let response = self.client.execute_raw::<()>(&url).await?;
-        self.handle_orionoid_response(response).await
+        self.search_page(api_token, stream_query, page).await
     }
 
+    #[tracing::instrument(
+        skip_all,
+        fields(query = query.unwrap_or_default(), ?ids, ?season, ?ep, offset = page.offset, limit = page.limit),
+    )]
     pub async fn search_tv(
         &self,
         api_token: &str,
         query: Option<&str>,
-        imdbid: Option<&str>,
+        ids: MediaIds<'_>,
         season: Option<u32>,
         ep: Option<u32>,
-        max_results: u32,
+        page: PageRequest,
     ) -> Result<OrionApiResponse, Error> {
-        if api_token.is_empty() {
-            return Err(Error::Custom("API key is required".to_string()));
+        let mut stream_query = OrionStreamQuery {
+            season,
+            episode: ep,
+            ..OrionStreamQuery::new(OrionMediaType::Show)
+        };
+        Self::apply_search_terms(&mut stream_query, query, &ids);
+
+        self.search_page(api_token, stream_query, page).await
+    }
+
+    /// Retrieves the Orionoid pages covering the requested window and trims them down to it.
+    async fn search_page(
+        &self,
+        api_token: &str,
+        mut stream_query: OrionStreamQuery,
+        page: PageRequest,
+    ) -> Result<OrionApiResponse, Error> {
+        let paging = Paging::from_page_request(page);
+        Self::apply_paging(&mut stream_query, paging.count, paging.page);
+        let mut api_response = self.search_streams(api_token, &stream_query).await?;
+
+        let retrieved = api_response.data.as_ref().and_then(|d| d.streams.as_ref()).map_or(0, Vec::len);
+        if paging.skip == 0 || retrieved < paging.count as usize || paging.page == u32::MAX {
+            Self::skip_streams(&mut api_response, paging.skip);
+            return Ok(api_response);
         }
 
-        let final_query = Some(query.unwrap_or(DEFAULT_TV_QUERY).to_lowercase().to_string());
+        Self::apply_paging(&mut stream_query, paging.count, paging.page + 1);
+        let next = self.search_streams(api_token, &stream_query).await?;
+        let next_streams = next.data.and_then(|d| d.streams).unwrap_or_default();
+        if let Some(streams) = api_response.data.as_mut().and_then(|d| d.streams.as_mut()) {
+            streams.extend(next_streams);
+        }
 
-        let mut url = format!(
-            "/?keyapp=FGJKJFEBRHEMRFGSBGDLFPRGED96LJJL&keyuser={api_token}&streamtype=torrent&mode=stream&action=retrieve&type=show",
-            api_token = api_token,
-        );
+        Self::skip_streams(&mut api_response, paging.skip);
+        if let Some(streams) = api_response.data.as_mut().and_then(|d| d.streams.as_mut()) {
+            streams.truncate(paging.count as usize);
+        }
+        Ok(api_response)
+    }
 
-        if imdbid.is_some() {
-            self.append_query_param(&mut url, "idimdb", &imdbid);
-        } else {
-            self.append_query_param(&mut url, "query", &final_query);
+    /// Retrieves the streams matching an arbitrary query.
+    #[tracing::instrument(
+        skip_all,
+        fields(media_type = ?stream_query.media_type, page = ?stream_query.limit_page, count = ?stream_query.limit_count),
+    )]
+    pub async fn search_streams(&self, api_token: &str, stream_query: &OrionStreamQuery) -> Result<OrionApiResponse, Error> {
+        if api_token.trim().is_empty() {
+            return Err(Error::Unauthorized("API key is required".to_string()));
+        }
+
+        let path = stream_query
+            .to_path(self.client.app_key(), api_token)
+            .map_err(|e| Error::Custom(format!("Failed to encode Orionoid query: {e}")))?;
+
+        // The latest streams change constantly, so only lookups of specific media are persisted
+        self.retrieve(api_token, &path, stream_query.has_search_terms()).await
+    }
+
+    async fn retrieve(&self, api_token: &str, url: &str, persist: bool) -> Result<OrionApiResponse, Error> {
+        if persist {
+            // Anything stored before error responses were told apart is skipped, rather than served for hours
+            if let Some(stored) = self.client.stored_response(url).await.filter(OrionApiResponse::is_success) {
+                return Ok(stored);
+            }
         }
-        self.append_query_param(&mut url, "numberseason", &season);
-        self.append_query_param(&mut url, "numberepisode", &ep);
 
-        url.push_str(&format!("&limitcount={}", max_results));
+        let quota_tracker = self.client.quota_tracker();
+        if quota_tracker.is_exhausted(api_token) {
+            return Err(Error::RequestLimitReached("Orionoid daily request limit reached".to_string()));
+        }
 
+        let response = self.client.execute_raw(url).await?;
+        let api_response = match self.handle_orionoid_response(response).await {
+            Err(Error::RequestLimitReached(message)) => {
+                quota_tracker.mark_exhausted(api_token);
+                return Err(Error::RequestLimitReached(message));
+            }
+            result => result?,
+        };
 
-        let response = self.client.execute_raw::<()>(&url).await?;
-        self.handle_orionoid_response(response).await
+        if let Some(requests) = api_response.data.as_ref().and_then(|data| data.requests.as_ref()) {
+            quota_tracker.record(api_token, requests);
+        }
+
+        if persist && api_response.is_success() {
+            self.client.store_response(url, &api_response).await;
+        }
+        Ok(api_response)
     }
 
     async fn handle_orionoid_response(
         &self,
         response: reqwest::Response,
     ) -> Result<OrionApiResponse, Error> {
+        let status = response.status();
         let body = response.text().await.map_err(Error::from)?;
-        debug!("Parsing response: {:?}", body);
+        debug!("Parsing Orionoid response ({} bytes, {})", body.len(), status);
 
-        let json: serde_json::Value = serde_json::from_str(&body).map_err(Error::from)?;
+        // Orionoid reports failures in the result of the body, sometimes alongside an error status
+        let result = serde_json::from_str::<OrionResultEnvelope>(&body).ok().and_then(|envelope| envelope.result);
+        if let Some(result) = result.filter(|result| result.status.as_deref() == Some("error")) {
+            return Err(orionoid_error(status, &result));
+        }
 
-        if let Some(result) = json.get("result") {
-            if result.get("status") == Some(&serde_json::Value::String("error".to_string()))
-                && result.get("type") == Some(&serde_json::Value::String("userkey".to_string()))
-            {
-                return Err(Error::Unauthorized("Invalid User API Key".to_string()));
-            }
+        if !status.is_success() {
+            return Err(status_error(status));
         }
 
         let api_response: OrionApiResponse = serde_json::from_str(&body).map_err(Error::from)?;
         Ok(api_response)
     }
 
-    fn append_query_param<T: ToString>(&self, url: &mut String, key: &str, value: &Option<T>) {
-        if let Some(val) = value {
-            url.push_str(&format!("&{}={}", key, val.to_string()));
+    /// Searches by ids when there are any, then by title. Without either, the most recently added streams are
+    /// retrieved instead, which is what RSS sync in the *arrs asks for.
+    fn apply_search_terms(stream_query: &mut OrionStreamQuery, query: Option<&str>, ids: &MediaIds<'_>) {
+        let query = Self::search_query(query);
+
+        if !ids.is_empty() {
+            Self::apply_media_ids(stream_query, ids);
+        } else if let Some(query) = query {
+            stream_query.query = Some(query.to_lowercase());
+        } else {
+            stream_query.season = None;
+            stream_query.episode = None;
+            stream_query.sort_value = Some(OrionSortValue::TimeAdded);
+            stream_query.sort_order = Some(OrionSortOrder::Descending);
+        }
+    }
+
+    fn search_query(query: Option<&str>) -> Option<&str> {
+        query.map(str::trim).filter(|q| !q.is_empty())
+    }
+
+    fn apply_media_ids(stream_query: &mut OrionStreamQuery, ids: &MediaIds<'_>) {
+        stream_query.imdb = ids.imdb.map(str::to_string);
+        stream_query.tmdb = ids.tmdb.map(str::to_string);
+        stream_query.tvdb = ids.tvdb.map(str::to_string);
+        stream_query.trakt = ids.trakt.map(str::to_string);
+        stream_query.tvrage = ids.tvrage.map(str::to_string);
+    }
+
+    fn apply_paging(stream_query: &mut OrionStreamQuery, count: u32, page: u32) {
+        stream_query.limit_count = Some(count);
+        stream_query.limit_page = Some(page);
+    }
+
+    fn skip_streams(api_response: &mut OrionApiResponse, skip: u32) {
+        if skip == 0 {
+            return;
+        }
+
+        if let Some(streams) = api_response.data.as_mut().and_then(|d| d.streams.as_mut()) {
+            let skip = (skip as usize).min(streams.len());
+            streams.drain(..skip);
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_request_page_when_offset_is_aligned() {
+        assert_eq!(Paging::from_page_request(PageRequest { offset: 0, limit: 50 }), Paging { count: 50, page: 1, skip: 0 });
+        assert_eq!(Paging::from_page_request(PageRequest { offset: 100, limit: 50 }), Paging { count: 50, page: 3, skip: 0 });
+    }
+
+    #[test]
+    fn should_start_from_the_page_an_unaligned_offset_falls_in() {
+        assert_eq!(Paging::from_page_request(PageRequest { offset: 30, limit: 50 }), Paging { count: 50, page: 1, skip: 30 });
+        assert_eq!(Paging::from_page_request(PageRequest { offset: 130, limit: 50 }), Paging { count: 50, page: 3, skip: 30 });
+    }
+
+    #[test]
+    fn should_not_overflow_on_huge_offsets() {
+        assert_eq!(
+            Paging::from_page_request(PageRequest { offset: u32::MAX - 5, limit: 50 }),
+            Paging { count: 50, page: (u32::MAX - 5) / 50 + 1, skip: (u32::MAX - 5) % 50 },
+        );
+        assert_eq!(Paging::from_page_request(PageRequest { offset: u32::MAX, limit: 1 }), Paging { count: 1, page: u32::MAX, skip: 0 });
+    }
+
+    fn result(error_type: &str) -> OrionResult {
+        OrionResult {
+            status: Some("error".to_string()),
+            result_type: Some(error_type.to_string()),
+            description: Some(format!("{error_type} failed")),
+            message: None,
+        }
+    }
+
+    #[test]
+    fn should_map_orionoid_error_types() {
+        assert!(matches!(orionoid_error(StatusCode::OK, &result("userkey")), Error::Unauthorized(_)));
+        assert!(matches!(orionoid_error(StatusCode::OK, &result("userlimit")), Error::RequestLimitReached(_)));
+        assert!(matches!(orionoid_error(StatusCode::OK, &result("usersuspended")), Error::AccountSuspended(_)));
+        assert!(matches!(orionoid_error(StatusCode::OK, &result("medianotfound")), Error::NotFound(_)));
+        assert!(matches!(orionoid_error(StatusCode::OK, &result("maintenance")), Error::ServiceUnavailable(_)));
+        assert!(matches!(orionoid_error(StatusCode::OK, &result("appkey")), Error::Server { .. }));
+        assert!(matches!(orionoid_error(StatusCode::OK, &result("parameter")), Error::Validation(_)));
+        assert!(matches!(orionoid_error(StatusCode::BAD_GATEWAY, &result("unknown")), Error::Server { code: 502, .. }));
+    }
+
+    #[test]
+    fn should_map_error_statuses() {
+        assert!(matches!(status_error(StatusCode::FORBIDDEN), Error::Unauthorized(_)));
+        assert!(matches!(status_error(StatusCode::TOO_MANY_REQUESTS), Error::RequestLimitReached(_)));
+        assert!(matches!(status_error(StatusCode::SERVICE_UNAVAILABLE), Error::ServiceUnavailable(_)));
+        assert!(matches!(status_error(StatusCode::NOT_FOUND), Error::NotFound(_)));
+        assert!(matches!(status_error(StatusCode::BAD_REQUEST), Error::Validation(_)));
+        assert!(matches!(status_error(StatusCode::INTERNAL_SERVER_ERROR), Error::Server { code: 500, .. }));
+    }
+}
@@ -0,0 +1,192 @@
+use serde::Serialize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OrionMediaType {
+    Movie,
+    Show,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OrionStreamType {
+    Torrent,
+    Usenet,
+    Hoster,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OrionSortValue {
+    Best,
+    Shuffle,
+    Popularity,
+    TimeAdded,
+    TimeUpdated,
+    VideoQuality,
+    AudioChannels,
+    FileSize,
+    StreamSeeds,
+    StreamAge,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OrionSortOrder {
+    Ascending,
+    Descending,
+}
+
+/// The parameters of an Orionoid stream retrieval, serialized into a properly encoded query string.
+///
+/// Unset parameters are left out, so Orionoid applies its own defaults.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct OrionStreamQuery {
+    #[serde(rename = "type")]
+    pub media_type: OrionMediaType,
+    #[serde(rename = "streamtype", skip_serializing_if = "Option::is_none")]
+    pub stream_type: Option<OrionStreamType>,
+    /// A free text title search, only used when no ids are given
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub query: Option<String>,
+    #[serde(rename = "idimdb", skip_serializing_if = "Option::is_none")]
+    pub imdb: Option<String>,
+    #[serde(rename = "idtmdb", skip_serializing_if = "Option::is_none")]
+    pub tmdb: Option<String>,
+    #[serde(rename = "idtvdb", skip_serializing_if = "Option::is_none")]
+    pub tvdb: Option<String>,
+    #[serde(rename = "idtrakt", skip_serializing_if = "Option::is_none")]
+    pub trakt: Option<String>,
+    #[serde(rename = "idtvrage", skip_serializing_if = "Option::is_none")]
+    pub tvrage: Option<String>,
+    #[serde(rename = "numberseason", skip_serializing_if = "Option::is_none")]
+    pub season: Option<u32>,
+    #[serde(rename = "numberepisode", skip_serializing_if = "Option::is_none")]
+    pub episode: Option<u32>,
+    /// Streams per page
+    #[serde(rename = "limitcount", skip_serializing_if = "Option::is_none")]
+    pub limit_count: Option<u32>,
+    /// The 1-based page of streams to retrieve
+    #[serde(rename = "limitpage", skip_serializing_if = "Option::is_none")]
+    pub limit_page: Option<u32>,
+    #[serde(rename = "sortvalue", skip_serializing_if = "Option::is_none")]
+    pub sort_value: Option<OrionSortValue>,
+    #[serde(rename = "sortorder", skip_serializing_if = "Option::is_none")]
+    pub sort_order: Option<OrionSortOrder>,
+    /// A quality range or list, e.g. `hd720_hd4k` or `hd1080,hd4k`
+    #[serde(rename = "videoquality", skip_serializing_if = "Option::is_none")]
+    pub video_quality: Option<String>,
+    /// A codec list, e.g. `h264,h265`
+    #[serde(rename = "videocodec", skip_serializing_if = "Option::is_none")]
+    pub video_codec: Option<String>,
+    /// A channel range, e.g. `6_8`
+    #[serde(rename = "audiochannels", skip_serializing_if = "Option::is_none")]
+    pub audio_channels: Option<String>,
+    /// A size range in bytes, e.g. `1073741824_10737418240`
+    #[serde(rename = "filesize", skip_serializing_if = "Option::is_none")]
+    pub file_size: Option<String>,
+    /// The minimum number of seeds
+    #[serde(rename = "streamseeds", skip_serializing_if = "Option::is_none")]
+    pub stream_seeds: Option<u32>,
+    /// Debrid services the streams must be cached on, e.g. `realdebrid,premiumize`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub access: Option<String>,
+}
+
+impl OrionStreamQuery {
+    /// A torrent search for the media type, with every other parameter unset.
+    pub fn new(media_type: OrionMediaType) -> Self {
+        Self {
+            media_type,
+            stream_type: Some(OrionStreamType::Torrent),
+            query: None,
+            imdb: None,
+            tmdb: None,
+            tvdb: None,
+            trakt: None,
+            tvrage: None,
+            season: None,
+            episode: None,
+            limit_count: None,
+            limit_page: None,
+            sort_value: None,
+            sort_order: None,
+            video_quality: None,
+            video_codec: None,
+            audio_channels: None,
+            file_size: None,
+            stream_seeds: None,
+            access: None,
+        }
+    }
+
+    /// Whether the query looks up specific media, rather than the latest streams of the media type.
+    pub fn has_search_terms(&self) -> bool {
+        self.query.is_some()
+            || self.imdb.is_some()
+            || self.tmdb.is_some()
+            || self.tvdb.is_some()
+            || self.trakt.is_some()
+            || self.tvrage.is_some()
+    }
+
+    /// The request path retrieving the streams, authenticated with the app and user keys.
+    pub fn to_path(&self, app_key: &str, user_key: &str) -> Result<String, serde_urlencoded::ser::Error> {
+        #[derive(Serialize)]
+        struct Retrieve<'a> {
+            keyapp: &'a str,
+            keyuser: &'a str,
+            mode: &'static str,
+            action: &'static str,
+            #[serde(flatten)]
+            query: &'a OrionStreamQuery,
+        }
+
+        let query = serde_urlencoded::to_string(Retrieve {
+            keyapp: app_key,
+            keyuser: user_key,
+            mode: "stream",
+            action: "retrieve",
+            query: self,
+        })?;
+        Ok(format!("/?{query}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_encode_query_values() {
+        let query = OrionStreamQuery {
+            query: Some("fast & furious #7 + café".to_string()),
+            sort_value: Some(OrionSortValue::VideoQuality),
+            sort_order: Some(OrionSortOrder::Ascending),
+            limit_count: Some(50),
+            limit_page: Some(1),
+            ..OrionStreamQuery::new(OrionMediaType::Movie)
+        };
+
+        assert_eq!(
+            query.to_path("APP", "USER").unwrap(),
+            "/?keyapp=APP&keyuser=USER&mode=stream&action=retrieve&type=movie&streamtype=torrent\
+             &query=fast+%26+furious+%237+%2B+caf%C3%A9&limitcount=50&limitpage=1&sortvalue=videoquality&sortorder=ascending",
+        );
+    }
+
+    #[test]
+    fn should_omit_unset_parameters() {
+        let query = OrionStreamQuery {
+            tvdb: Some("81189".to_string()),
+            season: Some(1),
+            episode: Some(2),
+            ..OrionStreamQuery::new(OrionMediaType::Show)
+        };
+
+        assert_eq!(
+            query.to_path("APP", "USER").unwrap(),
+            "/?keyapp=APP&keyuser=USER&mode=stream&action=retrieve&type=show&streamtype=torrent&idtvdb=81189&numberseason=1&numberepisode=2",
+        );
+    }
+}
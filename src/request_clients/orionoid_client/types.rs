@@ -1,6 +1,6 @@
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OrionApiResponse {
     pub name: Option<String>,
     pub version: Option<String>,
@@ -8,7 +8,37 @@ pub struct OrionApiResponse {
     pub data: Option<OrionData>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+impl OrionApiResponse {
+    /// Whether Orionoid reported the request as successful, which is the only kind of response worth keeping.
+    pub fn is_success(&self) -> bool {
+        self.result.as_ref().and_then(|result| result.status.as_deref()) == Some("success")
+    }
+
+    /// A successful response without any streams, for searches that can't match anything.
+    pub fn empty() -> Self {
+        Self {
+            name: None,
+            version: None,
+            result: Some(OrionResult {
+                status: Some("success".to_string()),
+                result_type: None,
+                description: None,
+                message: None,
+            }),
+            data: Some(OrionData {
+                data_type: None,
+                movie: None,
+                show: None,
+                episode: None,
+                count: Some(OrionCount { total: Some(0), requested: None, retrieved: Some(0) }),
+                streams: Some(Vec::new()),
+                requests: None,
+            }),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OrionResult {
     pub status: Option<String>,
     #[serde(rename = "type")]
@@ -17,7 +47,7 @@ pub struct OrionResult {
     pub message: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OrionData {
     #[serde(rename = "type")]
     pub data_type: Option<String>,
@@ -29,7 +59,7 @@ pub struct OrionData {
     pub requests: Option<OrionRequests>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OrionMovie {
     pub id: Option<OrionMovieId>,
     pub time: Option<OrionTime>,
@@ -37,14 +67,14 @@ pub struct OrionMovie {
     pub popularity: Option<OrionPopularity>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OrionMovieId {
     pub orion: Option<String>,
     pub imdb: Option<String>,
     pub tmdb: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OrionShow {
     #[serde(rename = "type")]
     pub show_type: Option<String>,
@@ -54,7 +84,7 @@ pub struct OrionShow {
     pub meta: Option<OrionMeta>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OrionShowId {
     pub orion: Option<String>,
     pub imdb: Option<String>,
@@ -65,7 +95,7 @@ pub struct OrionShowId {
     pub slug: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OrionEpisode {
     #[serde(rename = "type")]
     pub episode_type: Option<String>,
@@ -76,7 +106,7 @@ pub struct OrionEpisode {
     pub meta: Option<OrionMeta>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OrionEpisodeId {
     pub orion: Option<String>,
     pub imdb: Option<String>,
@@ -87,38 +117,39 @@ pub struct OrionEpisodeId {
     pub slug: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OrionEpisodeNumber {
     pub season: Option<u32>,
     pub episode: Option<u32>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OrionTime {
     pub added: Option<u64>,
     pub updated: Option<u64>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OrionMeta {
     pub title: Option<String>,
     pub year: Option<u16>,
+    pub genres: Option<Vec<String>>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OrionPopularity {
     pub count: Option<u32>,
     pub percent: Option<f64>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OrionCount {
     pub total: Option<u32>,
     pub requested: Option<u32>,
     pub retrieved: Option<u32>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OrionStream {
     pub id: Option<String>,
     pub time: Option<OrionTime>,
@@ -133,7 +164,7 @@ pub struct OrionStream {
     pub popularity: Option<OrionPopularity>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OrionStreamInfo {
     #[serde(rename = "type")]
     pub stream_type: Option<String>,
@@ -143,7 +174,7 @@ pub struct OrionStreamInfo {
     pub time: Option<u32>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OrionAccess {
     pub direct: Option<bool>,
     pub premiumize: Option<bool>,
@@ -155,7 +186,7 @@ pub struct OrionAccess {
     pub debridlink: Option<bool>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OrionFile {
     pub hash: Option<String>,
     pub name: Option<String>,
@@ -163,14 +194,14 @@ pub struct OrionFile {
     pub pack: Option<bool>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OrionStreamMeta {
     pub release: Option<String>,
     pub uploader: Option<String>,
     pub edition: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OrionVideo {
     pub quality: Option<String>,
     pub codec: Option<String>,
@@ -178,7 +209,7 @@ pub struct OrionVideo {
     pub is_3d: Option<bool>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OrionAudio {
     #[serde(rename = "type")]
     pub audio_type: Option<String>,
@@ -188,20 +219,20 @@ pub struct OrionAudio {
     pub languages: Option<Vec<String>>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OrionSubtitle {
     #[serde(rename = "type")]
     pub subtitle_type: Option<String>,
     pub languages: Option<Vec<String>>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OrionRequests {
     pub total: Option<u32>,
     pub daily: Option<OrionDailyRequests>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OrionDailyRequests {
     pub limit: Option<u32>,
     pub used: Option<u32>,
@@ -1,34 +1,121 @@
 use std::num::NonZeroU32;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use anyhow::anyhow;
 use futures::future::BoxFuture;
 use governor::clock::DefaultClock;
 use governor::{Quota, RateLimiter};
+use governor::state::keyed::DefaultKeyedStateStore;
 use governor::state::NotKeyed;
+use tracing::{debug, field, info_span, Instrument, Span};
+use crate::configuration::configuration_provider::AppConfig;
+use crate::observability::metrics_recorder::{ORIONOID_REQUESTS, ORIONOID_REQUEST_DURATION, RATE_LIMITER_WAIT};
 use crate::request_clients::request_errors::error::Error;
+use crate::utils::redaction::{redact_reqwest_error, redact_url};
+
+type DirectLimiter = RateLimiter<NotKeyed, governor::state::InMemoryState, DefaultClock>;
+type KeyedLimiter = RateLimiter<String, DefaultKeyedStateStore<String>, DefaultClock>;
+
+/// How many user keys the keyed limiter tracks before idle ones are pruned.
+const KEYED_LIMITER_PRUNE_THRESHOLD: usize = 1024;
 
 #[derive(Clone, Debug)]
 pub struct RateLimitedClient {
     inner: Arc<reqwest::Client>,
-    limiter: Arc<RateLimiter<NotKeyed, governor::state::InMemoryState, DefaultClock>>,
+    /// The ceiling across every caller of the deployment
+    limiter: Arc<DirectLimiter>,
+    /// Fair share per Orionoid user key, so one heavy caller can't starve the others
+    keyed_limiter: Arc<KeyedLimiter>,
 }
 
 pub trait Executor: Send + Sync {
-    fn execute_raw(&self, url: &str) -> BoxFuture<Result<reqwest::Response, Error>>;
+    fn execute_raw(&self, url: &str) -> BoxFuture<'_, Result<reqwest::Response, Error>>;
 }
 
 impl RateLimitedClient {
-    pub fn from_config(user_agent: &str, rate_limit: &str) -> anyhow::Result<Self> {
-        let client = Arc::new(
-            reqwest::Client::builder()
-                .user_agent(user_agent)
-                .build()
-                .map_err(|e| anyhow::anyhow!("Failed to build reqwest client: {e}"))?,
-        );
+    pub fn from_config(config: &AppConfig) -> anyhow::Result<Self> {
+        let client = Arc::new(Self::build_http_client(config)?);
+
+        let quota = Self::parse_quota_with_burst(&config.orionoid_rate_limit, config.orionoid_rate_limit_burst)?;
+        let keyed_quota = Self::parse_quota_with_burst(&config.orionoid_per_key_rate_limit, config.orionoid_per_key_rate_limit_burst)?;
+
+        let limiter = Arc::new(RateLimiter::direct(quota));
+        let keyed_limiter = Arc::new(RateLimiter::keyed(keyed_quota));
+
+        Ok(Self { inner: client, limiter, keyed_limiter })
+    }
+
+    fn build_http_client(config: &AppConfig) -> anyhow::Result<reqwest::Client> {
+        let mut builder = reqwest::Client::builder()
+            .user_agent(&config.user_agent)
+            .connect_timeout(Duration::from_secs(config.http_connect_timeout_seconds))
+            .read_timeout(Duration::from_secs(config.http_read_timeout_seconds))
+            .timeout(Duration::from_secs(config.http_request_timeout_seconds))
+            .pool_idle_timeout(Duration::from_secs(config.http_pool_idle_timeout_seconds))
+            .pool_max_idle_per_host(config.http_pool_max_idle_per_host);
+
+        builder = match config.http_version.trim().to_lowercase().as_str() {
+            // Negotiated through ALPN, preferring HTTP/2 when Orionoid offers it
+            "auto" | "" => builder,
+            "http1" => builder.http1_only(),
+            "http2" => builder.http2_prior_knowledge(),
+            other => anyhow::bail!("Invalid http version '{other}', must be 'auto', 'http1' or 'http2'"),
+        };
+
+        if let Some(proxy) = Self::build_proxy(config)? {
+            builder = builder.proxy(proxy);
+        }
+
+        builder
+            .build()
+            .map_err(|e| anyhow::anyhow!("Failed to build reqwest client: {e}"))
+    }
+
+    /// Routes every request through an `http://`, `https://`, `socks5://` or `socks5h://` proxy.
+    fn build_proxy(config: &AppConfig) -> anyhow::Result<Option<reqwest::Proxy>> {
+        let Some(proxy_url) = config.proxy_url.as_deref().map(str::trim).filter(|url| !url.is_empty()) else {
+            return Ok(None);
+        };
+
+        let mut url = reqwest::Url::parse(proxy_url)
+            .map_err(|e| anyhow::anyhow!("Invalid proxy url: {e}"))?;
+
+        if !matches!(url.scheme(), "http" | "https" | "socks5" | "socks5h") {
+            anyhow::bail!("Unsupported proxy scheme '{}'", url.scheme());
+        }
+
+        // Credentials travel in the url so they apply to SOCKS proxies as well as HTTP ones
+        if let Some(username) = config.proxy_username.as_deref().filter(|u| !u.is_empty()) {
+            url.set_username(username)
+                .map_err(|_| anyhow!("Proxy url cannot carry credentials"))?;
+            url.set_password(config.proxy_password.as_deref())
+                .map_err(|_| anyhow!("Proxy url cannot carry credentials"))?;
+        }
+
+        let proxy = reqwest::Proxy::all(url)
+            .map_err(|e| anyhow::anyhow!("Failed to configure proxy: {e}"))?;
 
-        let limiter = Arc::new(RateLimiter::direct(Self::parse_quota(rate_limit)?));
+        Ok(Some(proxy))
+    }
 
-        Ok(Self { inner: client, limiter })
+    fn parse_quota_with_burst(s: &str, burst: Option<u32>) -> anyhow::Result<Quota> {
+        let quota = Self::parse_quota(s)?;
+        match burst {
+            Some(burst) => {
+                let burst = NonZeroU32::new(burst).ok_or_else(|| anyhow!("Burst must be > 0"))?;
+                Ok(quota.allow_burst(burst))
+            }
+            None => Ok(quota),
+        }
+    }
+
+    /// The Orionoid user key a request is made on behalf of, taken from its `keyuser` query parameter.
+    fn user_key(url: &str) -> Option<String> {
+        let url = reqwest::Url::parse(url).ok()?;
+        url.query_pairs()
+            .find(|(key, _)| key == "keyuser")
+            .map(|(_, value)| value.into_owned())
+            .filter(|value| !value.is_empty())
     }
 
     fn parse_quota(s: &str) -> anyhow::Result<Quota> {
@@ -51,20 +138,82 @@ impl RateLimitedClient {
 }
 
 impl Executor for RateLimitedClient {
-    fn execute_raw(&self, url: &str) -> BoxFuture<Result<reqwest::Response, Error>> {
+    fn execute_raw(&self, url: &str) -> BoxFuture<'_, Result<reqwest::Response, Error>> {
         let client = self.inner.clone();
         let limiter = self.limiter.clone();
+        let keyed_limiter = self.keyed_limiter.clone();
         let url = url.to_string();
+        let span = info_span!("orionoid_request", url = %redact_url(&url), status = field::Empty);
 
         Box::pin(async move {
+            let waiting_since = Instant::now();
+            if let Some(user_key) = Self::user_key(&url) {
+                keyed_limiter.until_key_ready(&user_key).await;
+                if keyed_limiter.len() > KEYED_LIMITER_PRUNE_THRESHOLD {
+                    keyed_limiter.retain_recent();
+                }
+            }
             limiter.until_ready().await;
-            client
+            metrics::histogram!(RATE_LIMITER_WAIT).record(waiting_since.elapsed());
+
+            debug!("Sending request to Orionoid");
+            let sent_at = Instant::now();
+            let result = client
                 .get(&url)
                 .send()
                 .await
                 .map_err(|e| Error::Request {
-                    source: Box::new(e),
-                })
-        })
+                    source: Box::new(redact_reqwest_error(e)),
+                });
+            metrics::histogram!(ORIONOID_REQUEST_DURATION).record(sent_at.elapsed());
+
+            let status = match &result {
+                Ok(response) => response.status().as_u16().to_string(),
+                Err(_) => "error".to_string(),
+            };
+            Span::current().record("status", status.as_str());
+            metrics::counter!(ORIONOID_REQUESTS, "status" => status).increment(1);
+
+            result
+        }.instrument(span))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RateLimitedClient;
+
+    #[test]
+    fn should_extract_user_key() {
+        assert_eq!(
+            RateLimitedClient::user_key("https://api.orionoid.com/?keyapp=app&keyuser=abc123&mode=stream"),
+            Some("abc123".to_string())
+        );
+        assert_eq!(RateLimitedClient::user_key("https://api.orionoid.com/?keyuser=&mode=stream"), None);
+        assert_eq!(RateLimitedClient::user_key("https://api.orionoid.com/?mode=stream"), None);
+    }
+
+    #[test]
+    fn should_configure_proxies() {
+        use crate::configuration::configuration_provider::AppConfig;
+
+        let config = AppConfig {
+            proxy_url: Some("socks5://proxy.local:1080".to_string()),
+            proxy_username: Some("user".to_string()),
+            proxy_password: Some("secret".to_string()),
+            ..AppConfig::default()
+        };
+        assert!(RateLimitedClient::build_proxy(&config).unwrap().is_some());
+
+        let config = AppConfig { proxy_url: Some("ftp://proxy.local".to_string()), ..AppConfig::default() };
+        assert!(RateLimitedClient::build_proxy(&config).is_err());
+
+        assert!(RateLimitedClient::build_proxy(&AppConfig::default()).unwrap().is_none());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn should_reject_zero_burst() {
+        assert!(RateLimitedClient::parse_quota_with_burst("10/second", Some(0)).is_err());
+        assert!(RateLimitedClient::parse_quota_with_burst("10/second", Some(20)).is_ok());
+    }
+}
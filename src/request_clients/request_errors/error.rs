@@ -1,3 +1,4 @@
+use crate::utils::redaction::redact_reqwest_error;
 use crate::request_clients::request_errors::server_other_body_error::ServerOtherBodyError;
 use crate::request_clients::request_errors::server_validation_error::ServerValidationBodyError;
 
@@ -20,6 +21,10 @@ pub enum Error {
     Reqwest(reqwest::Error),
     SerdeJson(serde_json::Error),
     Unauthorized(String),
+    AccountSuspended(String),
+    NotFound(String),
+    RequestLimitReached(String),
+    ServiceUnavailable(String),
     Custom(String),
 }
 
@@ -29,10 +34,14 @@ impl std::fmt::Display for Error {
             Error::Request { .. } => write!(f, "couldn't execute request"),
             Error::Response { .. } => write!(f, "couldn't read response"),
             Error::Validation(err) => write!(f, "validation failed: {}", err),
-            Error::Server { code, content } => write!(f, "internal server error with code {}: {}", code, content),
+            Error::Server { code, content } => write!(f, "server error with code {}: {}", code, content.status_message),
             Error::Reqwest(err) => write!(f, "reqwest error: {}", err),
             Error::SerdeJson(err) => write!(f, "serde_json error: {}", err),
             Error::Unauthorized(msg) => write!(f, "401 Unauthorized: {}", msg),
+            Error::AccountSuspended(msg) => write!(f, "403 Forbidden: {}", msg),
+            Error::NotFound(msg) => write!(f, "404 Not Found: {}", msg),
+            Error::RequestLimitReached(msg) => write!(f, "429 Too Many Requests: {}", msg),
+            Error::ServiceUnavailable(msg) => write!(f, "503 Service Unavailable: {}", msg),
             Error::Custom(msg) => write!(f, "{}", msg),
         }
     }
@@ -44,7 +53,7 @@ impl std::error::Error for Error {}
 
 impl From<reqwest::Error> for Error {
     fn from(err: reqwest::Error) -> Self {
-        Error::Reqwest(err)
+        Error::Reqwest(redact_reqwest_error(err))
     }
 }
 
@@ -0,0 +1,297 @@
+use std::time::Duration;
+use futures::future::BoxFuture;
+use reqwest::header::RETRY_AFTER;
+use tracing::{debug, warn};
+use crate::configuration::configuration_provider::AppConfig;
+use crate::request_clients::rate_limited_client::Executor;
+use crate::request_clients::request_errors::error::Error;
+use crate::utils::redaction::redact_reqwest_error;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Total attempts made for a request, including the first
+    pub max_attempts: u32,
+    /// The delay before the first retry, doubled on every retry after that
+    pub base_delay: Duration,
+    /// The longest the policy will ever wait between attempts. A `Retry-After` asking for longer is not retried at all
+    pub max_delay: Duration,
+    /// Response statuses that are worth another attempt
+    pub retryable_status_codes: Vec<u16>,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(250),
+            max_delay: Duration::from_secs(5),
+            retryable_status_codes: vec![429, 500, 502, 503, 504],
+        }
+    }
+}
+
+impl RetryPolicy {
+    pub fn from_config(config: &AppConfig) -> anyhow::Result<Self> {
+        let retryable_status_codes = config.retry_status_codes
+            .split(',')
+            .map(str::trim)
+            .filter(|code| !code.is_empty())
+            .map(|code| code.parse::<u16>().map_err(|e| anyhow::anyhow!("Invalid retry status code '{code}': {e}")))
+            .collect::<anyhow::Result<Vec<u16>>>()?;
+
+        Ok(Self {
+            max_attempts: config.retry_max_attempts.max(1),
+            base_delay: Duration::from_millis(config.retry_base_delay_ms),
+            max_delay: Duration::from_millis(config.retry_max_delay_ms),
+            retryable_status_codes,
+        })
+    }
+
+    /// Exponential backoff with full jitter, so concurrent retries don't hit Orionoid in lockstep.
+    fn backoff(&self, retry: u32) -> Duration {
+        let exponential = self.base_delay.saturating_mul(2u32.saturating_pow(retry));
+        let ceiling = exponential.min(self.max_delay);
+        ceiling.mul_f64(fastrand::f64())
+    }
+
+    /// How long to wait before the next attempt, or `None` when Orionoid asked for a longer wait than the policy allows,
+    /// since retrying any sooner would only be rejected again and cost quota.
+    fn delay_for(&self, retry: u32, retry_after: Option<Duration>) -> Option<Duration> {
+        match retry_after {
+            Some(retry_after) if retry_after > self.max_delay => None,
+            Some(retry_after) => Some(retry_after),
+            None => Some(self.backoff(retry)),
+        }
+    }
+
+    fn is_retryable_status(&self, status: reqwest::StatusCode) -> bool {
+        self.retryable_status_codes.contains(&status.as_u16())
+    }
+}
+
+/// Retries transient failures of any [`Executor`] it wraps.
+#[derive(Clone, Debug)]
+pub struct RetryingClient<E> {
+    inner: E,
+    policy: RetryPolicy,
+}
+
+impl<E: Executor> RetryingClient<E> {
+    pub fn new(inner: E, policy: RetryPolicy) -> Self {
+        Self { inner, policy }
+    }
+
+    /// Reads a `Retry-After` header given in seconds, or as an HTTP date.
+    fn retry_after(response: &reqwest::Response) -> Option<Duration> {
+        let value = response.headers().get(RETRY_AFTER)?.to_str().ok()?.trim();
+        if let Ok(seconds) = value.parse::<u64>() {
+            return Some(Duration::from_secs(seconds));
+        }
+
+        let date = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+        (date.with_timezone(&chrono::Utc) - chrono::Utc::now()).to_std().ok()
+    }
+
+    /// Reads the whole body up front, so a read that times out or drops part way through is retried with the request.
+    async fn buffer(response: reqwest::Response) -> Result<reqwest::Response, Error> {
+        let mut buffered = axum::http::Response::builder()
+            .status(response.status())
+            .version(response.version());
+        if let Some(headers) = buffered.headers_mut() {
+            headers.extend(response.headers().clone());
+        }
+
+        let body = response.bytes().await.map_err(|e| Error::Response {
+            source: Box::new(redact_reqwest_error(e)),
+        })?;
+        let buffered = buffered
+            .body(body)
+            .map_err(|e| Error::Response { source: Box::new(e) })?;
+
+        Ok(reqwest::Response::from(buffered))
+    }
+}
+
+impl<E: Executor> Executor for RetryingClient<E> {
+    fn execute_raw(&self, url: &str) -> BoxFuture<'_, Result<reqwest::Response, Error>> {
+        let url = url.to_string();
+
+        Box::pin(async move {
+            let mut attempt = 1;
+            loop {
+                let is_last_attempt = attempt >= self.policy.max_attempts;
+                let result = match self.inner.execute_raw(&url).await {
+                    Ok(response) if !self.policy.is_retryable_status(response.status()) => Self::buffer(response).await,
+                    result => result,
+                };
+
+                let retry_after = match &result {
+                    Ok(response) if !is_last_attempt && self.policy.is_retryable_status(response.status()) => {
+                        debug!("Orionoid responded with {}, retrying", response.status());
+                        Self::retry_after(response)
+                    }
+                    Err(Error::Request { source } | Error::Response { source }) if !is_last_attempt => {
+                        debug!("Orionoid request failed, retrying: {source}");
+                        None
+                    }
+                    _ => return result,
+                };
+
+                let Some(delay) = self.policy.delay_for(attempt - 1, retry_after) else {
+                    warn!("Orionoid asked to retry after {:?}, longer than the retry policy allows, giving up", retry_after.unwrap_or_default());
+                    return result;
+                };
+                warn!("Retrying Orionoid request in {:?} (attempt {} of {})", delay, attempt + 1, self.policy.max_attempts);
+                tokio::time::sleep(delay).await;
+                attempt += 1;
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use super::*;
+
+    #[test]
+    fn should_cap_backoff_at_max_delay() {
+        let policy = RetryPolicy {
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_millis(500),
+            ..RetryPolicy::default()
+        };
+
+        for retry in 0..10 {
+            assert!(policy.backoff(retry) <= Duration::from_millis(500));
+        }
+        assert!(policy.backoff(0) <= Duration::from_millis(100));
+    }
+
+    #[test]
+    fn should_honor_retry_after_only_up_to_max_delay() {
+        let policy = RetryPolicy::default();
+
+        assert_eq!(policy.delay_for(0, Some(Duration::from_secs(2))), Some(Duration::from_secs(2)));
+        assert_eq!(policy.delay_for(0, Some(Duration::from_secs(60))), None);
+    }
+
+    fn response(status: u16) -> reqwest::Response {
+        reqwest::Response::from(
+            axum::http::Response::builder()
+                .status(status)
+                .header(RETRY_AFTER, "3")
+                .body("")
+                .unwrap(),
+        )
+    }
+
+    #[test]
+    fn should_parse_retry_after_seconds() {
+        assert_eq!(RetryingClient::<FlakyExecutor>::retry_after(&response(503)), Some(Duration::from_secs(3)));
+    }
+
+    /// Fails with a transport error until it has been called `failures` times.
+    struct FlakyExecutor {
+        failures: u32,
+        calls: AtomicU32,
+    }
+
+    impl Executor for FlakyExecutor {
+        fn execute_raw(&self, _url: &str) -> BoxFuture<'_, Result<reqwest::Response, Error>> {
+            let call = self.calls.fetch_add(1, Ordering::SeqCst);
+            let failures = self.failures;
+            Box::pin(async move {
+                if call < failures {
+                    Err(Error::Request { source: "connection reset".into() })
+                } else {
+                    Ok(response(200))
+                }
+            })
+        }
+    }
+
+    fn fast_policy(max_attempts: u32) -> RetryPolicy {
+        RetryPolicy {
+            max_attempts,
+            base_delay: Duration::from_millis(1),
+            max_delay: Duration::from_millis(5),
+            ..RetryPolicy::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn should_retry_transient_failures() {
+        let client = RetryingClient::new(FlakyExecutor { failures: 2, calls: AtomicU32::new(0) }, fast_policy(3));
+
+        let response = client.execute_raw("https://api.orionoid.com").await.unwrap();
+        assert_eq!(response.status(), 200);
+        assert_eq!(client.inner.calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn should_give_up_after_max_attempts() {
+        let client = RetryingClient::new(FlakyExecutor { failures: 5, calls: AtomicU32::new(0) }, fast_policy(2));
+
+        assert!(client.execute_raw("https://api.orionoid.com").await.is_err());
+        assert_eq!(client.inner.calls.load(Ordering::SeqCst), 2);
+    }
+
+    /// Always responds with a 503 asking to retry in 3 seconds.
+    struct UnavailableExecutor {
+        calls: AtomicU32,
+    }
+
+    impl Executor for UnavailableExecutor {
+        fn execute_raw(&self, _url: &str) -> BoxFuture<'_, Result<reqwest::Response, Error>> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            Box::pin(async { Ok(response(503)) })
+        }
+    }
+
+    #[tokio::test]
+    async fn should_give_up_when_retry_after_exceeds_max_delay() {
+        let client = RetryingClient::new(UnavailableExecutor { calls: AtomicU32::new(0) }, fast_policy(3));
+
+        let response = client.execute_raw("https://api.orionoid.com").await.unwrap();
+        assert_eq!(response.status(), 503);
+        assert_eq!(client.inner.calls.load(Ordering::SeqCst), 1);
+    }
+
+    /// Sends requests for real, so body reads can fail.
+    struct HttpExecutor {
+        client: reqwest::Client,
+        calls: AtomicU32,
+    }
+
+    impl Executor for HttpExecutor {
+        fn execute_raw(&self, url: &str) -> BoxFuture<'_, Result<reqwest::Response, Error>> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            let request = self.client.get(url).send();
+            Box::pin(async move { request.await.map_err(|e| Error::Request { source: Box::new(e) }) })
+        }
+    }
+
+    #[tokio::test]
+    async fn should_retry_truncated_bodies() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            // The first response promises more body than it sends before hanging up
+            for reply in ["content-length: 10\r\n\r\nhalf", "content-length: 4\r\n\r\nfull"] {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let _ = socket.read(&mut [0; 1024]).await;
+                let reply = format!("HTTP/1.1 200 OK\r\nconnection: close\r\n{reply}");
+                socket.write_all(reply.as_bytes()).await.unwrap();
+            }
+        });
+
+        let client = RetryingClient::new(HttpExecutor { client: reqwest::Client::new(), calls: AtomicU32::new(0) }, fast_policy(3));
+
+        let response = client.execute_raw(&url).await.unwrap();
+        assert_eq!(response.text().await.unwrap(), "full");
+        assert_eq!(client.inner.calls.load(Ordering::SeqCst), 2);
+    }
+}
@@ -0,0 +1,78 @@
+use std::future::Future;
+use axum::Router;
+use tracing::info;
+use crate::configuration::listen_address::ListenAddress;
+
+/// Serves the router on the address until `shutdown` resolves, then stops accepting connections and lets
+/// in-flight requests finish.
+pub async fn serve<F>(address: ListenAddress, router: Router, shutdown: F) -> std::io::Result<()>
+where
+    F: Future<Output = ()> + Send + 'static,
+{
+    match address {
+        ListenAddress::Tcp(addr) => {
+            let listener = tokio::net::TcpListener::bind(addr).await?;
+            info!("listening on {}", listener.local_addr()?);
+            axum::serve(listener, router)
+                .with_graceful_shutdown(shutdown)
+                .await
+        }
+        #[cfg(unix)]
+        ListenAddress::Unix(path) => {
+            remove_stale_socket(&path)?;
+            let listener = tokio::net::UnixListener::bind(&path)?;
+            info!("listening on unix:{}", path.display());
+            let result = axum::serve(listener, router)
+                .with_graceful_shutdown(shutdown)
+                .await;
+            let _ = std::fs::remove_file(&path);
+            result
+        }
+        #[cfg(not(unix))]
+        ListenAddress::Unix(path) => Err(std::io::Error::new(
+            std::io::ErrorKind::Unsupported,
+            format!("unix sockets are not supported on this platform: {}", path.display()),
+        )),
+    }
+}
+
+/// Removes a socket left behind by a previous run, which would otherwise fail the bind. Anything else at the path is
+/// left alone and fails instead, so a misconfigured path never deletes a file.
+#[cfg(unix)]
+fn remove_stale_socket(path: &std::path::Path) -> std::io::Result<()> {
+    use std::os::unix::fs::FileTypeExt;
+
+    match std::fs::symlink_metadata(path) {
+        Ok(metadata) if metadata.file_type().is_socket() => std::fs::remove_file(path),
+        Ok(_) => Err(std::io::Error::new(
+            std::io::ErrorKind::AlreadyExists,
+            format!("{} already exists and is not a unix socket", path.display()),
+        )),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(e),
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_only_remove_stale_sockets() {
+        let directory = std::env::temp_dir().join(format!("orionznab-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&directory).unwrap();
+
+        let socket = directory.join("orionznab.sock");
+        drop(std::os::unix::net::UnixListener::bind(&socket).unwrap());
+        remove_stale_socket(&socket).unwrap();
+        assert!(!socket.exists());
+        remove_stale_socket(&socket).unwrap();
+
+        let file = directory.join("orionznab.conf");
+        std::fs::write(&file, "keep me").unwrap();
+        assert!(remove_stale_socket(&file).is_err());
+        assert_eq!(std::fs::read_to_string(&file).unwrap(), "keep me");
+
+        let _ = std::fs::remove_dir_all(directory);
+    }
+}
@@ -38,4 +38,10 @@ pub static CATEGORIES: LazyLock<Vec<Category>> = LazyLock::new(|| {
 
 pub fn get_category_by_name(name: &str) -> Option<&Category> {
     CATEGORIES.iter().find(|c| c.name == name)
+}
+
+pub fn get_subcategory_by_name(name: &str) -> Option<&Subcategory> {
+    CATEGORIES.iter()
+        .flat_map(|c| c.subcategories.iter())
+        .find(|s| s.name == name)
 }
\ No newline at end of file
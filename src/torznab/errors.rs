@@ -0,0 +1,153 @@
+//! Error responses as described by the [Newznab spec](https://newznab.readthedocs.io/en/latest/misc/api/#newznab-error-codes).
+use std::str;
+use axum::response::{IntoResponse, Response};
+use reqwest::StatusCode;
+use xml::writer::{EmitterConfig, XmlEvent};
+use crate::request_clients::request_errors::error::Error;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorCode {
+    /// 100 - Incorrect user credentials
+    IncorrectCredentials,
+    /// 101 - Account suspended
+    AccountSuspended,
+    /// 200 - Missing parameter
+    MissingParameter,
+    /// 201 - Incorrect parameter
+    IncorrectParameter,
+    /// 202 - No such function
+    NoSuchFunction,
+    /// 300 - No such item
+    NoSuchItem,
+    /// 500 - Request limit reached
+    RequestLimitReached,
+    /// 900 - Unknown error
+    ///
+    /// The spec has no code for the indexer being unavailable, so Orionoid failing or being unreachable is reported with
+    /// this code too, told apart by the HTTP status: 502 when Orionoid failed, 503 while it is unavailable.
+    Unknown,
+}
+
+impl ErrorCode {
+    pub fn code(&self) -> u16 {
+        match self {
+            ErrorCode::IncorrectCredentials => 100,
+            ErrorCode::AccountSuspended => 101,
+            ErrorCode::MissingParameter => 200,
+            ErrorCode::IncorrectParameter => 201,
+            ErrorCode::NoSuchFunction => 202,
+            ErrorCode::NoSuchItem => 300,
+            ErrorCode::RequestLimitReached => 500,
+            ErrorCode::Unknown => 900,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TorznabError {
+    /// The Newznab error code
+    pub code: ErrorCode,
+    /// A human readable description of what went wrong
+    pub description: String,
+    /// The HTTP status the error is returned with
+    pub status: StatusCode,
+}
+
+impl TorznabError {
+    pub fn new(code: ErrorCode, description: impl Into<String>, status: StatusCode) -> Self {
+        Self {
+            code,
+            description: description.into(),
+            status,
+        }
+    }
+
+    pub fn missing_parameter(name: &str) -> Self {
+        Self::new(ErrorCode::MissingParameter, format!("Missing parameter: {name}"), StatusCode::BAD_REQUEST)
+    }
+
+    pub fn no_such_function(name: &str) -> Self {
+        Self::new(ErrorCode::NoSuchFunction, format!("No such function: {name}"), StatusCode::BAD_REQUEST)
+    }
+
+    pub fn to_xml(&self) -> String {
+        let mut writer = EmitterConfig::new().create_writer(Vec::new());
+        writer
+            .write(
+                XmlEvent::start_element("error")
+                    .attr("code", self.code.code().to_string().as_str())
+                    .attr("description", self.description.as_str()),
+            )
+            .unwrap();
+        writer.write(XmlEvent::end_element()).unwrap();
+
+        str::from_utf8(writer.into_inner().as_slice())
+            .unwrap()
+            .to_string()
+    }
+}
+
+impl std::fmt::Display for TorznabError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} ({})", self.description, self.code.code())
+    }
+}
+
+impl IntoResponse for TorznabError {
+    fn into_response(self) -> Response {
+        (
+            self.status,
+            [("content-type", "application/xml")],
+            self.to_xml(),
+        ).into_response()
+    }
+}
+
+impl From<Error> for TorznabError {
+    fn from(err: Error) -> Self {
+        match &err {
+            Error::Unauthorized(msg) => Self::new(ErrorCode::IncorrectCredentials, msg.clone(), StatusCode::UNAUTHORIZED),
+            Error::AccountSuspended(msg) => Self::new(ErrorCode::AccountSuspended, msg.clone(), StatusCode::FORBIDDEN),
+            Error::NotFound(msg) => Self::new(ErrorCode::NoSuchItem, msg.clone(), StatusCode::NOT_FOUND),
+            Error::RequestLimitReached(msg) => Self::new(ErrorCode::RequestLimitReached, msg.clone(), StatusCode::TOO_MANY_REQUESTS),
+            Error::Validation(_) => Self::new(ErrorCode::IncorrectParameter, format!("Orionoid rejected the search: {err}"), StatusCode::BAD_REQUEST),
+            Error::Request { .. } | Error::Reqwest(_) => Self::new(ErrorCode::Unknown, format!("Orionoid could not be reached: {err}"), StatusCode::BAD_GATEWAY),
+            Error::Response { .. } | Error::Server { .. } => Self::new(ErrorCode::Unknown, format!("Orionoid returned an error: {err}"), StatusCode::BAD_GATEWAY),
+            Error::SerdeJson(_) => Self::new(ErrorCode::Unknown, format!("Orionoid returned an unreadable response: {err}"), StatusCode::BAD_GATEWAY),
+            Error::ServiceUnavailable(msg) => Self::new(ErrorCode::Unknown, format!("Service unavailable: {msg}"), StatusCode::SERVICE_UNAVAILABLE),
+            Error::Custom(msg) => Self::new(ErrorCode::Unknown, msg.clone(), StatusCode::INTERNAL_SERVER_ERROR),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_render_spec_compliant_error() {
+        let error = TorznabError::from(Error::Unauthorized("Invalid \"User\" API Key".to_string()));
+
+        assert_eq!(error.status, StatusCode::UNAUTHORIZED);
+        assert!(error.to_xml().ends_with(r#"<error code="100" description="Invalid &quot;User&quot; API Key" />"#));
+    }
+
+    #[test]
+    fn should_map_suspended_accounts_and_missing_items() {
+        let suspended = TorznabError::from(Error::AccountSuspended("suspended".to_string()));
+        assert_eq!(suspended.code.code(), 101);
+        assert_eq!(suspended.status, StatusCode::FORBIDDEN);
+
+        let missing = TorznabError::from(Error::NotFound("missing".to_string()));
+        assert_eq!(missing.code.code(), 300);
+        assert_eq!(missing.status, StatusCode::NOT_FOUND);
+    }
+
+    #[test]
+    fn should_map_request_limit() {
+        let error = TorznabError::from(Error::RequestLimitReached("limit".to_string()));
+
+        assert_eq!(error.code.code(), 500);
+        assert_eq!(error.status, StatusCode::TOO_MANY_REQUESTS);
+    }
+}
@@ -0,0 +1,112 @@
+use std::sync::Arc;
+use axum::extract::{FromRef, State};
+use axum::Json;
+use axum::response::{IntoResponse, Response};
+use reqwest::StatusCode;
+use serde::Serialize;
+use crate::app_state::AppState;
+use crate::request_clients::circuit_breaker_client::CircuitState;
+use crate::request_clients::orionoid_client::orionoid_request_client::OrionoidRequestClient;
+
+#[derive(Debug, Clone)]
+pub struct HealthState {
+    pub orionoid_client: Arc<OrionoidRequestClient>,
+    /// Whether readiness also requires Orionoid itself to respond
+    pub check_upstream: bool,
+}
+
+impl FromRef<AppState> for HealthState {
+    fn from_ref(state: &AppState) -> Self {
+        Self {
+            orionoid_client: state.orionoid_client.clone(),
+            check_upstream: state.app_config.ready_check_upstream,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Readiness {
+    pub ready: bool,
+    pub circuit: CircuitState,
+    /// Only present when the upstream check is enabled
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub upstream_reachable: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Version {
+    pub version: &'static str,
+    pub git_sha: &'static str,
+    pub build_time: String,
+}
+
+/// Liveness - answers as long as the process is serving requests.
+#[tracing::instrument(skip_all)]
+pub async fn health() -> impl IntoResponse {
+    Json(serde_json::json!({ "status": "ok" }))
+}
+
+#[tracing::instrument(skip_all)]
+pub async fn ready(State(state): State<HealthState>) -> Response {
+    let circuit = state.orionoid_client.circuit_state();
+
+    let upstream_reachable = if state.check_upstream {
+        Some(state.orionoid_client.ping().await.is_ok())
+    } else {
+        None
+    };
+
+    let ready = is_ready(circuit, upstream_reachable);
+
+    let status = if ready { StatusCode::OK } else { StatusCode::SERVICE_UNAVAILABLE };
+
+    (status, Json(Readiness {
+        ready,
+        circuit,
+        upstream_reachable,
+    })).into_response()
+}
+
+/// A half-open circuit counts as ready, as it is only closed again by the next search getting through. Holding traffic
+/// back until then would leave the instance unready for good.
+fn is_ready(circuit: CircuitState, upstream_reachable: Option<bool>) -> bool {
+    circuit != CircuitState::Open && upstream_reachable.unwrap_or(true)
+}
+
+#[tracing::instrument(skip_all)]
+pub async fn version() -> Json<Version> {
+    let build_time = env!("ORIONZNAB_BUILD_TIMESTAMP")
+        .parse::<i64>()
+        .ok()
+        .and_then(|timestamp| chrono::DateTime::from_timestamp(timestamp, 0))
+        .map(|time| time.to_rfc3339())
+        .unwrap_or_default();
+
+    Json(Version {
+        version: env!("CARGO_PKG_VERSION"),
+        git_sha: env!("ORIONZNAB_GIT_SHA"),
+        build_time,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+    use crate::request_clients::circuit_breaker_client::CircuitBreaker;
+    use super::*;
+
+    #[tokio::test]
+    async fn should_be_ready_again_once_the_circuit_half_opens() {
+        let breaker = CircuitBreaker::new(1, Duration::from_millis(20));
+        assert!(is_ready(breaker.state(), None));
+
+        breaker.record_failure();
+        assert_eq!(breaker.state(), CircuitState::Open);
+        assert!(!is_ready(breaker.state(), None));
+
+        tokio::time::sleep(Duration::from_millis(30)).await;
+        assert_eq!(breaker.state(), CircuitState::HalfOpen);
+        assert!(is_ready(breaker.state(), None));
+        assert!(!is_ready(breaker.state(), Some(false)));
+    }
+}
@@ -1,41 +1,86 @@
-mod types;
+pub mod types;
+pub mod errors;
 mod responses;
 mod categories;
 mod search_config;
 mod limits;
 mod search_handler;
+mod status;
+mod health;
 
 use axum::extract::{State, Query};
-use axum::response::IntoResponse;
+use axum::http::{HeaderMap, HeaderValue};
+use axum::response::{IntoResponse, Response};
 use axum::{Router, routing::get};
 use std::sync::Arc;
-use once_cell::sync::OnceCell;
-use reqwest::StatusCode;
+use std::time::Instant;
+use tracing::{debug, info_span, Instrument};
+use crate::app_state::AppState;
+use crate::configuration::listen_address::normalize_url_base;
+use crate::observability::metrics_recorder::{render_metrics, TORZNAB_REQUESTS};
 use crate::request_clients::orionoid_client::orionoid_request_client::OrionoidRequestClient;
+use crate::request_clients::orionoid_client::search_cache::SearchCache;
 use crate::torznab::types::{Config, SearchFunc};
 
-static TORZNAB_API: OnceCell<Router> = OnceCell::new();
-static SEARCH_HANDLER: OnceCell<Arc<search_handler::TorznabSearchHandler>> = OnceCell::new();
+const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// Reuses a well formed request id passed in by a proxy, so logs can be correlated across both.
+fn request_id(headers: &HeaderMap) -> String {
+    headers
+        .get(REQUEST_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .filter(|id| !id.is_empty() && id.len() <= 64 && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'))
+        .map(str::to_string)
+        .unwrap_or_else(|| uuid::Uuid::new_v4().to_string())
+}
 
 async fn api_dispatch(
+    headers: HeaderMap,
     State(conf): State<Arc<Config>>,
     Query(query): Query<responses::SearchForm>,
-) -> impl IntoResponse {
-    match query.t.as_deref() {
-        Some("caps") => responses::caps(State(conf)).await.into_response(),
-        Some("search") => responses::search(State(conf), Query(query)).await.into_response(),
-        Some("tvsearch") => responses::tv_search(State(conf), Query(query)).await.into_response(),
-        Some("movie") => responses::movie_search(State(conf), Query(query)).await.into_response(),
-        _ => (StatusCode::NOT_FOUND, "Unknown or missing `t` parameter").into_response(),
+) -> Response {
+    // Unknown functions share a label so arbitrary input can't grow the series count
+    let t: &'static str = match query.t.as_deref() {
+        Some("caps") => "caps",
+        Some("search") => "search",
+        Some("tvsearch") => "tvsearch",
+        Some("movie") => "movie",
+        Some(_) => "unknown",
+        None => "missing",
+    };
+    ::metrics::counter!(TORZNAB_REQUESTS, "t" => t).increment(1);
+
+    let request_id = request_id(&headers);
+    let span = info_span!("torznab_request", request_id = %request_id, t);
+    let started = Instant::now();
+
+    let mut response = async move {
+        match query.t.as_deref() {
+            Some("caps") => responses::caps(State(conf)).await.into_response(),
+            Some("search") => responses::search(State(conf), Query(query)).await.into_response(),
+            Some("tvsearch") => responses::tv_search(State(conf), Query(query)).await.into_response(),
+            Some("movie") => responses::movie_search(State(conf), Query(query)).await.into_response(),
+            Some(other) => errors::TorznabError::no_such_function(other).into_response(),
+            None => errors::TorznabError::missing_parameter("t").into_response(),
+        }
+    }
+    .instrument(span.clone())
+    .await;
+
+    span.in_scope(|| debug!("Responded with {} in {:?}", response.status(), started.elapsed()));
+
+    if let Ok(value) = HeaderValue::from_str(&request_id) {
+        response.headers_mut().insert(REQUEST_ID_HEADER, value);
     }
+    response
 }
 
-fn setup_torznab_config() -> Config {
-    let search_handler: SearchFunc = Arc::new(|params| {
-        Box::pin(async move {
-            let handler = SEARCH_HANDLER.get().expect("Handler not initialized");
-            handler.search_orionoid(params).await
-        })
+/// The torznab config searching Orionoid through the given client.
+pub fn torznab_config(orionoid_client: Arc<OrionoidRequestClient>, search_cache: SearchCache) -> Config {
+    let handler = Arc::new(search_handler::TorznabSearchHandler::new(orionoid_client, search_cache));
+    let search_handler: SearchFunc = Arc::new(move |params| {
+        let handler = handler.clone();
+        Box::pin(async move { handler.search_orionoid(params).await })
     });
 
     let caps = types::Caps {
@@ -55,25 +100,22 @@ fn setup_torznab_config() -> Config {
     }
 }
 
-pub fn initialize_torznab_api(orionoid_client: Arc<OrionoidRequestClient>) {
-    let search_handler = Arc::new(search_handler::TorznabSearchHandler::new(orionoid_client.clone()));
-    let state_config = setup_torznab_config();
-
-    let state = Arc::new(state_config);
+/// Builds the api router over the given state, nested under the configured url base.
+pub fn torznab_router(state: AppState) -> Router {
+    let url_base = normalize_url_base(&state.app_config.url_base);
 
-    let torznab_api = Router::new()
+    let routes = Router::new()
         .route("/api", get(api_dispatch))
+        .route("/status", get(status::status))
+        .route("/health", get(health::health))
+        .route("/ready", get(health::ready))
+        .route("/version", get(health::version))
+        .route("/metrics", get(render_metrics))
         .with_state(state);
 
-    SEARCH_HANDLER
-        .set(search_handler)
-        .expect("Search Handler already initialized");
-
-    TORZNAB_API
-        .set(torznab_api)
-        .expect("Torznab API already initialized");
+    if url_base.is_empty() {
+        routes
+    } else {
+        Router::new().nest(&url_base, routes)
+    }
 }
-
-pub fn get_torznab_api() -> Router {
-    TORZNAB_API.get().expect("Torznab Api not initialized").clone()
-}
\ No newline at end of file
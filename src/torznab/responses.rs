@@ -6,8 +6,6 @@ use serde::Deserialize;
 use std::sync::Arc;
 use std::borrow::Borrow;
 use std::str;
-use reqwest::StatusCode;
-use uuid::Uuid;
 use xml::writer::{EmitterConfig, XmlEvent};
 use crate::torznab::types::*;
 
@@ -17,7 +15,16 @@ pub struct SearchForm {
     pub q: Option<String>,
     pub apikey: Option<String>,
     pub cat: Option<String>,
+    #[serde(default, deserialize_with = "crate::utils::empty_strings::deserialize")]
     pub imdbid: Option<String>,
+    #[serde(default, deserialize_with = "crate::utils::empty_strings::deserialize")]
+    pub tvdbid: Option<String>,
+    #[serde(default, deserialize_with = "crate::utils::empty_strings::deserialize")]
+    pub tmdbid: Option<String>,
+    #[serde(default, deserialize_with = "crate::utils::empty_strings::deserialize")]
+    pub traktid: Option<String>,
+    #[serde(default, deserialize_with = "crate::utils::empty_strings::deserialize")]
+    pub rid: Option<String>,
     pub season: Option<u32>,
     pub ep: Option<u32>,
     pub attrs: Option<String>,
@@ -69,11 +76,15 @@ impl SearchForm {
             apikey: self.apikey.clone(),
             categories,
             imdbid: self.imdbid.clone(),
-            season: self.season.clone(),
-            ep: self.ep.clone(),
+            tvdbid: self.tvdbid.clone(),
+            tmdbid: self.tmdbid.clone(),
+            traktid: self.traktid.clone(),
+            rid: self.rid.clone(),
+            season: self.season,
+            ep: self.ep,
             attributes: extended_attribute_names.clone(),
             extended_attrs,
-            offset: self.offset.clone(),
+            offset: self.offset,
             limit,
         }
     }
@@ -84,27 +95,13 @@ pub struct RawXml<T>(pub T);
 
 impl<T: AsRef<str>> IntoResponse for RawXml<T> {
     fn into_response(self) -> Response {
-        let mut res = (
+        (
             [("content-type", "application/xml")],
             self.0.as_ref().to_owned(),
-        ).into_response();
-
-        if self.0.as_ref().contains("<error>Search failed: Orionoid search failed: Unauthorized(&quot;Invalid User API Key&quot;)</error>") {
-            *res.status_mut() = StatusCode::UNAUTHORIZED;
-        }
-
-        res
+        ).into_response()
     }
 }
 
-fn escape_xml(s: &str) -> String {
-    s.replace('&', "&amp;")
-        .replace('<', "&lt;")
-        .replace('>', "&gt;")
-        .replace('"', "&quot;")
-        .replace('\'', "&apos;")
-}
-
 pub async fn search(
     State(conf): State<Arc<Config>>,
     Query(form): Query<SearchForm>,
@@ -229,12 +226,26 @@ pub async fn caps(
 }
 
 
+/// The guid of an item, falling back to its download link, which is just as stable across requests.
+fn item_guid(item: &Torrent, torrent_file_url: &str, magnet_uri: &str) -> String {
+    item.guid.clone()
+        .unwrap_or_else(|| if torrent_file_url.is_empty() { magnet_uri.to_string() } else { torrent_file_url.to_string() })
+}
+
+/// When Orionoid added the item, if it said. Without a timestamp the `pubDate` is left out rather than made up.
+fn item_pub_date(item: &Torrent) -> Option<String> {
+    item.publish_date
+        .and_then(|timestamp| chrono::DateTime::from_timestamp(timestamp, 0))
+        .map(|date| date.to_rfc2822())
+}
+
 pub async fn search_handler(
     conf: &Config,
     form: SearchForm,
     search_type: &str,
-) -> RawXml<String> {
+) -> Response {
     let parameters = form.to_parameters(conf.clone(), search_type);
+    let offset = parameters.offset.unwrap_or(0);
     let buffer = Vec::new();
     let mut writer = EmitterConfig::new().create_writer(buffer);
 
@@ -280,8 +291,18 @@ pub async fn search_handler(
 
     // Handle errors gracefully
     match (conf.search_handler)(parameters).await {
-        Ok(items) => {
-            for item in items {
+        Ok(results) => {
+            let total = results.total.unwrap_or(offset + results.torrents.len() as u32);
+            writer
+                .write(
+                    XmlEvent::start_element("torznab:response")
+                        .attr("offset", offset.to_string().as_str())
+                        .attr("total", total.to_string().as_str()),
+                )
+                .unwrap();
+            writer.write(XmlEvent::end_element()).unwrap();
+
+            for item in results.torrents {
                 let torrent_file_url = item.torrent_file_url.clone().unwrap_or_default();
                 let magnet_uri = item.magnet_uri.clone().unwrap_or_default();
 
@@ -289,11 +310,8 @@ pub async fn search_handler(
                     continue;
                 }
 
-                let guid = Uuid::new_v4();
-                let guid_str = guid.to_string();
-
-                let now = chrono::Utc::now();
-                let pub_date = now.to_rfc2822();
+                let guid_str = item_guid(&item, &torrent_file_url, &magnet_uri);
+                let pub_date = item_pub_date(&item);
 
                 writer.write(XmlEvent::start_element("item")).unwrap();
 
@@ -301,7 +319,7 @@ pub async fn search_handler(
                 writer.write(XmlEvent::characters(&item.title)).unwrap();
                 writer.write(XmlEvent::end_element()).unwrap();
 
-                writer.write(XmlEvent::start_element("guid")).unwrap();
+                writer.write(XmlEvent::start_element("guid").attr("isPermaLink", "false")).unwrap();
                 writer.write(XmlEvent::characters(&guid_str)).unwrap();
                 writer.write(XmlEvent::end_element()).unwrap();
 
@@ -309,9 +327,11 @@ pub async fn search_handler(
                 writer.write(XmlEvent::characters(&item.result_type)).unwrap();
                 writer.write(XmlEvent::end_element()).unwrap();
 
-                writer.write(XmlEvent::start_element("pubDate")).unwrap();
-                writer.write(XmlEvent::characters(&pub_date)).unwrap();
-                writer.write(XmlEvent::end_element()).unwrap();
+                if let Some(pub_date) = &pub_date {
+                    writer.write(XmlEvent::start_element("pubDate")).unwrap();
+                    writer.write(XmlEvent::characters(pub_date)).unwrap();
+                    writer.write(XmlEvent::end_element()).unwrap();
+                }
 
                 if let Some(desc) = &item.description {
                     writer
@@ -390,12 +410,8 @@ pub async fn search_handler(
             }
         }
         Err(e) => {
-            // Return a minimal error XML, not RSS
-            let error_xml = format!(
-                r#"<?xml version="1.0" encoding="UTF-8"?><error>{}</error>"#,
-                escape_xml(&format!("Search failed: {e}"))
-            );
-            return RawXml(error_xml);
+            // Return an error document, not RSS
+            return e.into_response();
         }
     }
 
@@ -405,5 +421,47 @@ pub async fn search_handler(
         .unwrap()
         .to_string();
 
-    RawXml(result)
-}
\ No newline at end of file
+    RawXml(result).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn torrent(guid: Option<&str>, publish_date: Option<i64>) -> Torrent {
+        Torrent {
+            title: "Alien".to_string(),
+            description: None,
+            result_type: "movie".to_string(),
+            size: 1,
+            category_ids: vec![2000],
+            torrent_file_url: None,
+            magnet_uri: Some("magnet:?xt=urn:btih:abc".to_string()),
+            guid: guid.map(str::to_string),
+            publish_date,
+            other_attributes: None,
+        }
+    }
+
+    #[test]
+    fn should_ignore_empty_ids() {
+        let form: SearchForm = serde_urlencoded::from_str("t=tvsearch&q=severance&tvdbid=&imdbid=tt11280740").unwrap();
+
+        assert_eq!(form.tvdbid, None);
+        assert_eq!(form.imdbid.as_deref(), Some("tt11280740"));
+        assert_eq!(form.tmdbid, None);
+    }
+
+    #[test]
+    fn should_use_infohash_guid_and_fall_back_to_link() {
+        assert_eq!(item_guid(&torrent(Some("abc"), None), "", "magnet:?xt=urn:btih:abc"), "abc");
+        assert_eq!(item_guid(&torrent(None, None), "", "magnet:?xt=urn:btih:abc"), "magnet:?xt=urn:btih:abc");
+        assert_eq!(item_guid(&torrent(None, None), "https://example.com/a.torrent", "magnet:?xt=urn:btih:abc"), "https://example.com/a.torrent");
+    }
+
+    #[test]
+    fn should_only_date_items_with_a_timestamp() {
+        assert_eq!(item_pub_date(&torrent(None, Some(1_700_000_000))).as_deref(), Some("Tue, 14 Nov 2023 22:13:20 +0000"));
+        assert_eq!(item_pub_date(&torrent(None, None)), None);
+    }
+}
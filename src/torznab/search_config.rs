@@ -3,6 +3,10 @@ use crate::torznab::types::SearchInfo;
 
 static QUERY_FIELD: &str = "q";
 static IMDBID_FIELD: &str = "imdbid";
+static TVDBID_FIELD: &str = "tvdbid";
+static TMDBID_FIELD: &str = "tmdbid";
+static TRAKTID_FIELD: &str = "traktid";
+static RID_FIELD: &str = "rid";
 static SEASON_FIELD: &str = "season";
 static EPISODE_FIELD: &str = "ep";
 
@@ -22,6 +26,8 @@ pub static SEARCH_CONFIG: LazyLock<Vec<SearchInfo>> = LazyLock::new(|| {
             supported_params: vec![
                 QUERY_FIELD.to_string(),
                 IMDBID_FIELD.to_string(),
+                TMDBID_FIELD.to_string(),
+                TRAKTID_FIELD.to_string(),
             ],
         },
 
@@ -31,6 +37,10 @@ pub static SEARCH_CONFIG: LazyLock<Vec<SearchInfo>> = LazyLock::new(|| {
             supported_params: vec![
                 QUERY_FIELD.to_string(),
                 IMDBID_FIELD.to_string(),
+                TVDBID_FIELD.to_string(),
+                TMDBID_FIELD.to_string(),
+                TRAKTID_FIELD.to_string(),
+                RID_FIELD.to_string(),
                 SEASON_FIELD.to_string(),
                 EPISODE_FIELD.to_string(),
             ],
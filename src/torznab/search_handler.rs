@@ -1,16 +1,21 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
-use once_cell::sync::OnceCell;
+use tracing::warn;
+use crate::observability::metrics_recorder::{DROPPED_ITEMS, SEARCH_RESULTS};
 use crate::request_clients::orionoid_client::orionoid_request_client::{OrionoidRequestClient};
+use crate::request_clients::orionoid_client::search_cache::{SearchCache, SearchCacheKey};
+use crate::request_clients::orionoid_client::search_endpoints::{MediaIds, PageRequest};
 use crate::request_clients::orionoid_client::types::*;
+use crate::request_clients::request_errors::error::Error;
 use crate::torznab;
-use crate::torznab::categories::get_category_by_name;
-use crate::torznab::types::Torrent;
+use crate::torznab::errors::TorznabError;
+use crate::torznab::categories::{get_category_by_name, get_subcategory_by_name};
+use crate::torznab::types::{SearchResults, Torrent};
 
-static ORIONOID_API_CLIENT: OnceCell<Arc<OrionoidRequestClient>> = OnceCell::new();
-
-fn get_orionoid_client() -> Arc<OrionoidRequestClient> {
-    ORIONOID_API_CLIENT.get().expect("Orionoid API Client not initialized").clone()
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum MediaKind {
+    Movie,
+    Tv,
 }
 
 enum OrionIdRef<'a> {
@@ -46,17 +51,24 @@ fn map_orion_api_response_to_torrents(
         (None, None, None, false)
     };
 
+    let is_anime = is_anime(&data);
+
     let streams = match data.streams {
         Some(streams) => streams,
         None => return Vec::new(),
     };
 
-    let category_ids = if data.movie.is_some() {
-        get_category_by_name("Movies").map(|c| vec![c.id]).unwrap_or_default()
+    // Feeds of the latest streams aren't about a single movie or show, so only the data type says what they are
+    let media_kind = if data.movie.is_some() {
+        Some(MediaKind::Movie)
     } else if data.show.is_some() || data.episode.is_some() {
-        get_category_by_name("TV").map(|c| vec![c.id]).unwrap_or_default()
+        Some(MediaKind::Tv)
     } else {
-        Vec::new()
+        match data.data_type.as_deref() {
+            Some("movie") => Some(MediaKind::Movie),
+            Some("show") => Some(MediaKind::Tv),
+            _ => None,
+        }
     };
 
     streams.into_iter().filter_map(|stream| {
@@ -79,13 +91,16 @@ fn map_orion_api_response_to_torrents(
         } else {
             meta_main.and_then(|m| m.title.as_ref()).cloned()
         };
+        // Streams in a latest feed only carry their release name
+        let title = title.or_else(|| file.and_then(|f| f.name.clone()));
 
         if size.is_none() || title.is_none() || links.is_none() {
+            metrics::counter!(DROPPED_ITEMS).increment(1);
             return None;
         }
 
         let mut other_attributes = HashMap::new();
-        let mut result_type = "movie";
+        let result_type = if media_kind == Some(MediaKind::Tv) { "series" } else { "movie" };
         if let Some(id) = &id {
             match id {
                 OrionIdRef::Movie(id) => {
@@ -121,7 +136,6 @@ fn map_orion_api_response_to_torrents(
                     if let Some(slug) = &id.slug {
                         other_attributes.insert("slug".to_string(), slug.clone());
                     }
-                    result_type = "series";
                 }
                 OrionIdRef::Show(id) => {
                     if let Some(orion) = &id.orion {
@@ -145,7 +159,6 @@ fn map_orion_api_response_to_torrents(
                     if let Some(slug) = &id.slug {
                         other_attributes.insert("slug".to_string(), slug.clone());
                     }
-                    result_type = "series";
                 }
             }
         }
@@ -204,79 +217,580 @@ fn map_orion_api_response_to_torrents(
 
         other_attributes.insert("peers".to_string(), peers.to_string());
 
+        let category_ids = media_kind
+            .map(|kind| resolve_category_ids(kind, is_anime, &stream))
+            .unwrap_or_default();
+
+        let guid = file.and_then(|f| f.hash.as_ref())
+            .map(|hash| hash.to_lowercase())
+            .or_else(|| stream.id.clone());
+
+        let publish_date = stream.time.as_ref()
+            .and_then(|time| time.added.or(time.updated))
+            .and_then(|timestamp| i64::try_from(timestamp).ok());
+
         let links_vec = links.unwrap();
         Some(Torrent {
             title: title.unwrap(),
             description: None,
             result_type: result_type.to_string(),
             size: size.unwrap(),
-            category_ids: category_ids.clone(),
+            category_ids,
             torrent_file_url: links_vec.iter().find(|l| l.ends_with(".torrent")).cloned(),
             magnet_uri: links_vec.iter().find(|l| l.starts_with("magnet:")).cloned(),
+            guid,
+            publish_date,
             other_attributes: Some(other_attributes),
         })
     }).collect()
 }
 
+/// Resolves the Torznab subcategory name for a stream.
+///
+/// 3D and anime releases take precedence, then the video quality, then the release type.
+fn resolve_subcategory_name(kind: MediaKind, is_anime: bool, stream: &OrionStream) -> &'static str {
+    let video = stream.video.as_ref();
+    let release_type = stream.meta.as_ref()
+        .and_then(|m| m.release.as_deref())
+        .map(str::to_lowercase);
+
+    if kind == MediaKind::Movie && video.and_then(|v| v.is_3d).unwrap_or(false) {
+        return "Movies/3D";
+    }
+
+    if kind == MediaKind::Tv && is_anime {
+        return "TV/Anime";
+    }
+
+    let quality = video
+        .and_then(|v| v.quality.as_deref())
+        .map(str::to_lowercase);
+
+    let by_quality = match quality.as_deref() {
+        Some("hd8k" | "hd6k" | "hd4k" | "hd2k") => Some(("Movies/UHD", "TV/UHD")),
+        Some("hd1080" | "hd720") => Some(("Movies/HD", "TV/HD")),
+        Some("sd") => Some(("Movies/SD", "TV/SD")),
+        Some(q) if q.starts_with("scr") || q.starts_with("cam") => Some(("Movies/Other", "TV/Other")),
+        _ => None,
+    };
+
+    let by_release = || match release_type.as_deref() {
+        Some(r) if r.contains("bluray") || r.starts_with("bd") => Some(("Movies/BluRay", "TV/HD")),
+        Some(r) if r.contains("dvd") => Some(("Movies/DVD", "TV/SD")),
+        Some(r) if r.starts_with("web") => Some(("Movies/WEB-DL", "TV/WEB-DL")),
+        _ => None,
+    };
+
+    let (movie, tv) = by_quality
+        .or_else(by_release)
+        .unwrap_or(("Movies/Other", "TV/Other"));
+
+    match kind {
+        MediaKind::Movie => movie,
+        MediaKind::Tv => tv,
+    }
+}
+
+/// Whether Orionoid describes the show as anime, either by its type or by its genres.
+fn is_anime(data: &OrionData) -> bool {
+    let show_type = data.show.as_ref().and_then(|show| show.show_type.as_deref());
+    let episode_type = data.episode.as_ref().and_then(|episode| episode.episode_type.as_deref());
+    let genres = [
+        data.show.as_ref().and_then(|show| show.meta.as_ref()),
+        data.episode.as_ref().and_then(|episode| episode.meta.as_ref()),
+    ]
+        .into_iter()
+        .flatten()
+        .filter_map(|meta| meta.genres.as_ref())
+        .flatten();
+
+    show_type.into_iter()
+        .chain(episode_type)
+        .chain(genres.map(String::as_str))
+        .any(|value| value.eq_ignore_ascii_case("anime"))
+}
+
+/// Returns the parent category id followed by the resolved subcategory id for a stream.
+fn resolve_category_ids(kind: MediaKind, is_anime: bool, stream: &OrionStream) -> Vec<u32> {
+    let parent = match kind {
+        MediaKind::Movie => get_category_by_name("Movies"),
+        MediaKind::Tv => get_category_by_name("TV"),
+    };
+
+    let mut ids: Vec<u32> = parent.map(|c| vec![c.id]).unwrap_or_default();
+    if let Some(subcategory) = get_subcategory_by_name(resolve_subcategory_name(kind, is_anime, stream)) {
+        ids.push(subcategory.id);
+    }
+    ids
+}
+
+/// Whether the requested categories include the kind's parent category or any of its subcategories. An empty request includes everything.
+fn requests_media_kind(kind: MediaKind, requested: &[u32]) -> bool {
+    let name = match kind {
+        MediaKind::Movie => "Movies",
+        MediaKind::Tv => "TV",
+    };
+
+    requested.is_empty() || get_category_by_name(name).is_some_and(|category| {
+        requested.contains(&category.id)
+            || category.subcategories.iter().any(|subcategory| requested.contains(&subcategory.id))
+    })
+}
+
+/// Whether the requested categories leave out some of the kind's results, so they have to be filtered after retrieval.
+fn narrows_media_kind(kind: MediaKind, requested: &[u32]) -> bool {
+    let parent = match kind {
+        MediaKind::Movie => get_category_by_name("Movies"),
+        MediaKind::Tv => get_category_by_name("TV"),
+    };
+
+    !requested.is_empty() && parent.is_none_or(|category| !requested.contains(&category.id))
+}
+
+/// Whether a torrent falls within any of the requested categories. An empty request matches everything.
+fn matches_requested_categories(torrent: &Torrent, requested: &[u32]) -> bool {
+    requested.is_empty() || torrent.category_ids.iter().any(|id| requested.contains(id))
+}
+
+/// The most Orionoid pages scanned to fill a page of results that categories filter down.
+const MAX_FILTERED_PAGES: u32 = 5;
+
+/// Generic searches merge movies and TV from their first result on, so only this many results can be paged through.
+const MAX_COMBINED_RESULTS: u32 = 500;
+
+/// What a search looks Orionoid up by.
+#[derive(Debug, Clone, Copy)]
+struct SearchTerms<'a> {
+    query: Option<&'a str>,
+    ids: MediaIds<'a>,
+    season: Option<u32>,
+    ep: Option<u32>,
+}
+
 #[derive(Debug)]
-pub struct TorznabSearchHandler {}
+pub struct TorznabSearchHandler {
+    orionoid_client: Arc<OrionoidRequestClient>,
+    search_cache: SearchCache,
+}
 
 impl TorznabSearchHandler {
-    pub fn new(orionoid_client: Arc<OrionoidRequestClient>) -> Self {
-        ORIONOID_API_CLIENT.set(orionoid_client).expect("Failed to set Orionoid API Client");
-        Self {}
+    pub fn new(orionoid_client: Arc<OrionoidRequestClient>, search_cache: SearchCache) -> Self {
+        Self { orionoid_client, search_cache }
     }
 
-    pub async fn search_orionoid(&self, params: torznab::types::SearchParameters) -> Result<Vec<Torrent>, String> {
-        let api_token = match params.apikey.as_deref() {
-            Some(token) => token,
-            None => return Err("API key is required for search".to_string()),
+    #[tracing::instrument(name = "search_orionoid", skip_all, fields(search_type = %params.search_type))]
+    pub async fn search_orionoid(&self, params: torznab::types::SearchParameters) -> Result<SearchResults, TorznabError> {
+        let api_token = match params.apikey.as_deref().map(str::trim) {
+            Some(token) if !token.is_empty() => token,
+            _ => return Err(TorznabError::missing_parameter("apikey")),
+        };
+        let page = PageRequest {
+            offset: params.offset.unwrap_or(0),
+            limit: params.limit,
+        };
+        let ids = MediaIds {
+            imdb: params.imdbid.as_deref(),
+            tmdb: params.tmdbid.as_deref(),
+            tvdb: params.tvdbid.as_deref(),
+            trakt: params.traktid.as_deref(),
+            tvrage: params.rid.as_deref(),
+        };
+
+        let terms = SearchTerms {
+            query: params.q.as_deref(),
+            ids,
+            season: params.season,
+            ep: params.ep,
         };
-        let client = get_orionoid_client();
-        let max_results = params.limit;
 
-        let results = match params.search_type.as_str() {
-            "search" => {
-                client
-                    .search_endpoints()
-                    .search_movie(
-                        api_token,
-                        params.q.as_deref(),
-                        params.imdbid.as_deref(),
-                        max_results,
-                    )
-                    .await
+        let requested_categories = params.categories.clone().unwrap_or_default();
+
+        let (torrents, total) = match params.search_type.as_str() {
+            "search" => self.search_all(api_token, params.q.as_deref(), ids, page, &requested_categories).await?,
+            "movie" => self.search_media(MediaKind::Movie, api_token, terms, page, &requested_categories).await?,
+            "tvsearch" => self.search_media(MediaKind::Tv, api_token, terms, page, &requested_categories).await?,
+            other => return Err(TorznabError::no_such_function(other)),
+        };
+        metrics::histogram!(SEARCH_RESULTS).record(torrents.len() as f64);
+
+        Ok(SearchResults { torrents, total })
+    }
+
+    /// Searches one kind of media. When the requested categories filter out some of its results, the page is
+    /// taken from the filtered results instead, so pages stay full and the total only counts what matches.
+    async fn search_media(
+        &self,
+        kind: MediaKind,
+        api_token: &str,
+        terms: SearchTerms<'_>,
+        page: PageRequest,
+        requested_categories: &[u32],
+    ) -> Result<(Vec<Torrent>, Option<u32>), Error> {
+        if !requests_media_kind(kind, requested_categories) {
+            return Ok((Vec::new(), Some(0)));
+        }
+
+        if !narrows_media_kind(kind, requested_categories) {
+            let response = self.fetch(kind, api_token, terms, page).await?;
+            return Ok(into_torrents(response, requested_categories));
+        }
+
+        let (torrents, total) = self.scan_filtered(kind, api_token, terms, page, requested_categories).await?;
+        let torrents = torrents
+            .into_iter()
+            .skip(page.offset as usize)
+            .take(page.limit.max(1) as usize)
+            .collect();
+
+        Ok((torrents, total))
+    }
+
+    /// Collects the filtered results up to the end of the requested page, a page of Orionoid results at a time.
+    ///
+    /// The total is exact once Orionoid runs out of results, and otherwise counts the results not yet scanned as if they
+    /// all matched, so clients keep paging until a page comes back empty. When the scan gives up before the end of the
+    /// requested page, the total is only what was found, since paging can't reach any further.
+    async fn scan_filtered(
+        &self,
+        kind: MediaKind,
+        api_token: &str,
+        terms: SearchTerms<'_>,
+        page: PageRequest,
+        requested_categories: &[u32],
+    ) -> Result<(Vec<Torrent>, Option<u32>), Error> {
+        let limit = page.limit.max(1);
+        let wanted = page.offset.saturating_add(limit) as usize;
+
+        let mut torrents = Vec::new();
+        let mut scanned: u32 = 0;
+
+        for index in 0..MAX_FILTERED_PAGES {
+            let chunk = PageRequest { offset: index * limit, limit };
+            let response = self.fetch(kind, api_token, terms, chunk).await?;
+            let retrieved = retrieved_streams(&response);
+            let (matched, upstream_total) = into_torrents(response, requested_categories);
+            torrents.extend(matched);
+            scanned += retrieved;
+
+            if retrieved < limit || upstream_total.is_some_and(|total| scanned >= total) {
+                let total = torrents.len() as u32;
+                return Ok((torrents, Some(total)));
+            }
+            if torrents.len() >= wanted {
+                let total = upstream_total.map(|total| torrents.len() as u32 + total.saturating_sub(scanned));
+                return Ok((torrents, total));
+            }
+        }
+
+        let total = torrents.len() as u32;
+        Ok((torrents, Some(total)))
+    }
+
+    async fn fetch(
+        &self,
+        kind: MediaKind,
+        api_token: &str,
+        terms: SearchTerms<'_>,
+        page: PageRequest,
+    ) -> Result<OrionApiResponse, Error> {
+        match kind {
+            MediaKind::Movie => self.search_movies(api_token, terms.query, terms.ids, page).await,
+            MediaKind::Tv => self.search_shows(api_token, terms.query, terms.ids, terms.season, terms.ep, page).await,
+        }
+    }
+
+    /// Generic searches cover both movies and TV, unless the requested categories only ask for one of them.
+    async fn search_all(
+        &self,
+        api_token: &str,
+        query: Option<&str>,
+        ids: MediaIds<'_>,
+        page: PageRequest,
+        requested_categories: &[u32],
+    ) -> Result<(Vec<Torrent>, Option<u32>), Error> {
+        // Both searches fetch everything up to the end of the requested page, so the offset can be applied to the merged results
+        let window = page.offset.saturating_add(page.limit.max(1)).min(MAX_COMBINED_RESULTS);
+        let combined_page = PageRequest { offset: 0, limit: window };
+
+        let (movies, shows) = futures::join!(
+            async {
+                if !requests_media_kind(MediaKind::Movie, requested_categories) || !searches_movies(query, ids) {
+                    return Ok(None);
+                }
+                self.search_movies(api_token, query, ids, combined_page).await.map(Some)
+            },
+            async {
+                if !requests_media_kind(MediaKind::Tv, requested_categories) {
+                    return Ok(None);
+                }
+                self.search_shows(api_token, query, ids, None, None, combined_page).await.map(Some)
+            },
+        );
+
+        // A failed search is only worth hiding when the other one actually has results to show
+        let responses = match (movies, shows) {
+            (Err(e), Err(_)) | (Err(e), Ok(None)) | (Ok(None), Err(e)) => return Err(e),
+            (Ok(Some(response)), Err(e)) | (Err(e), Ok(Some(response))) => {
+                warn!("Generic search is only returning part of its results: {e}");
+                vec![Some(response)]
             }
-            "movie" => {
-                client
+            (Ok(movies), Ok(shows)) => vec![movies, shows],
+        };
+
+        let (results, totals): (Vec<Vec<Torrent>>, Vec<Option<u32>>) = responses
+            .into_iter()
+            .flatten()
+            .map(|response| {
+                let retrieved = retrieved_streams(&response);
+                let (torrents, total) = into_torrents(response, requested_categories);
+                // Everything Orionoid has fit in the window, so only what survived the category filter counts
+                let total = if retrieved < window { Some(torrents.len() as u32) } else { total };
+                (torrents, total)
+            })
+            .unzip();
+
+        let (merged, duplicates) = merge_unique(results);
+        let total = totals
+            .into_iter()
+            .flatten()
+            .reduce(|a, b| a.saturating_add(b))
+            .map(|total| total.saturating_sub(duplicates).min(MAX_COMBINED_RESULTS));
+
+        let torrents = merged
+            .into_iter()
+            .skip(page.offset as usize)
+            .take(page.limit.max(1) as usize)
+            .collect();
+
+        Ok((torrents, total))
+    }
+
+    async fn search_movies(
+        &self,
+        api_token: &str,
+        query: Option<&str>,
+        ids: MediaIds<'_>,
+        page: PageRequest,
+    ) -> Result<OrionApiResponse, Error> {
+        let key = SearchCacheKey::new(api_token, "movie", query, ids, None, None, page);
+        self.search_cache
+            .get_or_fetch(key, async {
+                self.orionoid_client
                     .search_endpoints()
-                    .search_movie(
-                        api_token,
-                        params.q.as_deref(),
-                        params.imdbid.as_deref(),
-                        max_results,
-                    )
+                    .search_movie(api_token, query, ids, page)
                     .await
-            }
-            "tvsearch" => {
-                client
+            })
+            .await
+    }
+
+    async fn search_shows(
+        &self,
+        api_token: &str,
+        query: Option<&str>,
+        ids: MediaIds<'_>,
+        season: Option<u32>,
+        ep: Option<u32>,
+        page: PageRequest,
+    ) -> Result<OrionApiResponse, Error> {
+        let key = SearchCacheKey::new(api_token, "show", query, ids, season, ep, page);
+        self.search_cache
+            .get_or_fetch(key, async {
+                self.orionoid_client
                     .search_endpoints()
-                    .search_tv(
-                        api_token,
-                        params.q.as_deref(),
-                        params.imdbid.as_deref(),
-                        params.season,
-                        params.ep,
-                        max_results,
-                    )
+                    .search_tv(api_token, query, ids, season, ep, page)
                     .await
+            })
+            .await
+    }
+}
+
+/// How many streams Orionoid returned, before any are dropped or filtered out.
+fn retrieved_streams(response: &OrionApiResponse) -> u32 {
+    response.data.as_ref()
+        .and_then(|data| data.streams.as_ref())
+        .map_or(0, |streams| streams.len() as u32)
+}
+
+/// Movies can only be looked up by title or imdb, tmdb and trakt ids, or listed latest first without any search terms.
+fn searches_movies(query: Option<&str>, ids: MediaIds<'_>) -> bool {
+    let has_query = query.is_some_and(|q| !q.trim().is_empty());
+    let has_movie_ids = ids.imdb.is_some() || ids.tmdb.is_some() || ids.trakt.is_some();
+    has_query || has_movie_ids || (ids.tvdb.is_none() && ids.tvrage.is_none())
+}
+
+/// Maps a response to the torrents within the requested categories, along with the total Orionoid reported.
+fn into_torrents(response: OrionApiResponse, requested_categories: &[u32]) -> (Vec<Torrent>, Option<u32>) {
+    let total = response.data.as_ref()
+        .and_then(|data| data.count.as_ref())
+        .and_then(|count| count.total);
+
+    let torrents = map_orion_api_response_to_torrents(response)
+        .into_iter()
+        .filter(|torrent| matches_requested_categories(torrent, requested_categories))
+        .collect();
+
+    (torrents, total)
+}
+
+/// Interleaves results so neither list crowds the other out of a page, dropping repeats of the same infohash.
+/// Returns the merged results along with how many repeats were dropped.
+fn merge_unique(results: Vec<Vec<Torrent>>) -> (Vec<Torrent>, u32) {
+    let mut seen = HashSet::new();
+    let mut merged = Vec::new();
+    let mut duplicates = 0;
+    let mut iterators: Vec<_> = results.into_iter().map(Vec::into_iter).collect();
+
+    loop {
+        let mut exhausted = true;
+        for torrent in iterators.iter_mut().filter_map(Iterator::next) {
+            exhausted = false;
+            if torrent.guid.as_ref().is_none_or(|guid| seen.insert(guid.clone())) {
+                merged.push(torrent);
+            } else {
+                duplicates += 1;
             }
-            _ => return Err("Unsupported search type".to_string()),
-        };
+        }
+        if exhausted {
+            return (merged, duplicates);
+        }
+    }
+}
 
-        match results {
-            Ok(response) => Ok(map_orion_api_response_to_torrents(response)),
-            Err(e) => Err(format!("Orionoid search failed: {:?}", e)),
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stream(value: serde_json::Value) -> OrionStream {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn should_resolve_movie_subcategories_from_quality() {
+        let uhd = stream(serde_json::json!({ "video": { "quality": "hd4k" } }));
+        assert_eq!(resolve_category_ids(MediaKind::Movie, false, &uhd), vec![2000, 2045]);
+
+        let hd = stream(serde_json::json!({ "video": { "quality": "hd1080" } }));
+        assert_eq!(resolve_category_ids(MediaKind::Movie, false, &hd), vec![2000, 2040]);
+
+        let cam = stream(serde_json::json!({ "video": { "quality": "cam720" } }));
+        assert_eq!(resolve_category_ids(MediaKind::Movie, false, &cam), vec![2000, 2020]);
+
+        let three_d = stream(serde_json::json!({ "video": { "quality": "hd1080", "3d": true } }));
+        assert_eq!(resolve_category_ids(MediaKind::Movie, false, &three_d), vec![2000, 2060]);
+    }
+
+    #[test]
+    fn should_resolve_tv_subcategories() {
+        let sd = stream(serde_json::json!({ "video": { "quality": "sd" } }));
+        assert_eq!(resolve_category_ids(MediaKind::Tv, false, &sd), vec![5000, 5030]);
+
+        let tagged = stream(serde_json::json!({
+            "video": { "quality": "hd1080" },
+            "file": { "name": "[TGx] Severance S02E10 1080p WEB h264" }
+        }));
+        assert_eq!(resolve_category_ids(MediaKind::Tv, false, &tagged), vec![5000, 5040]);
+        assert_eq!(resolve_category_ids(MediaKind::Tv, true, &tagged), vec![5000, 5070]);
+
+        let web = stream(serde_json::json!({ "meta": { "release": "WEBDL" } }));
+        assert_eq!(resolve_category_ids(MediaKind::Tv, false, &web), vec![5000, 5010]);
+    }
+
+    fn torrent(title: &str, guid: Option<&str>) -> Torrent {
+        Torrent {
+            title: title.to_string(),
+            description: None,
+            result_type: "movie".to_string(),
+            size: 1,
+            category_ids: vec![2000, 2045],
+            torrent_file_url: None,
+            magnet_uri: None,
+            guid: guid.map(str::to_string),
+            publish_date: None,
+            other_attributes: None,
         }
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn should_match_requested_categories() {
+        let torrent = torrent("test", None);
+
+        assert!(matches_requested_categories(&torrent, &[]));
+        assert!(matches_requested_categories(&torrent, &[2000]));
+        assert!(matches_requested_categories(&torrent, &[2040, 2045]));
+        assert!(!matches_requested_categories(&torrent, &[2040]));
+        assert!(!matches_requested_categories(&torrent, &[5000]));
+    }
+
+    #[test]
+    fn should_title_latest_streams_by_release_name() {
+        let response: OrionApiResponse = serde_json::from_value(serde_json::json!({
+            "data": {
+                "type": "show",
+                "streams": [{
+                    "links": ["magnet:?xt=urn:btih:abc"],
+                    "file": { "hash": "ABC", "name": "Severance.S02E10.1080p.WEB.h264", "size": 1024 },
+                    "video": { "quality": "hd1080" }
+                }]
+            }
+        })).unwrap();
+
+        let torrents = map_orion_api_response_to_torrents(response);
+
+        assert_eq!(torrents.len(), 1);
+        assert_eq!(torrents[0].title, "Severance.S02E10.1080p.WEB.h264");
+        assert_eq!(torrents[0].result_type, "series");
+        assert_eq!(torrents[0].category_ids, vec![5000, 5040]);
+    }
+
+    #[test]
+    fn should_search_media_kinds_in_requested_categories() {
+        assert!(requests_media_kind(MediaKind::Movie, &[]));
+        assert!(requests_media_kind(MediaKind::Tv, &[]));
+        assert!(requests_media_kind(MediaKind::Movie, &[2045]));
+        assert!(!requests_media_kind(MediaKind::Tv, &[2000, 2045]));
+        assert!(requests_media_kind(MediaKind::Tv, &[5000]));
+        assert!(!requests_media_kind(MediaKind::Movie, &[8000]));
+    }
+
+    #[test]
+    fn should_only_filter_when_categories_leave_results_out() {
+        assert!(!narrows_media_kind(MediaKind::Movie, &[]));
+        assert!(!narrows_media_kind(MediaKind::Movie, &[2000, 2045]));
+        assert!(narrows_media_kind(MediaKind::Movie, &[2045]));
+        assert!(narrows_media_kind(MediaKind::Tv, &[2000, 5040]));
+    }
+
+    #[test]
+    fn should_interleave_and_dedupe_merged_results() {
+        let movies = vec![torrent("movie 1", Some("a")), torrent("movie 2", Some("b")), torrent("movie 3", None)];
+        let shows = vec![torrent("show 1", Some("a")), torrent("show 2", Some("c"))];
+
+        let (merged, duplicates) = merge_unique(vec![movies, shows]);
+        let titles: Vec<String> = merged.into_iter().map(|t| t.title).collect();
+
+        assert_eq!(titles, vec!["movie 1", "movie 2", "show 2", "movie 3"]);
+        assert_eq!(duplicates, 1);
+    }
+
+    #[test]
+    fn should_only_search_movies_by_terms_that_apply_to_them() {
+        let tvdb = MediaIds { tvdb: Some("371980"), ..Default::default() };
+
+        assert!(searches_movies(None, MediaIds::default()));
+        assert!(searches_movies(Some("severance"), tvdb));
+        assert!(searches_movies(None, MediaIds { imdb: Some("tt11280740"), ..tvdb }));
+        assert!(!searches_movies(None, tvdb));
+        assert!(!searches_movies(Some(" "), MediaIds { tvrage: Some("1"), ..Default::default() }));
+    }
+
+    #[test]
+    fn should_detect_anime_from_orionoid_metadata() {
+        let data = |show: serde_json::Value| -> OrionData {
+            serde_json::from_value(serde_json::json!({ "show": show })).unwrap()
+        };
+
+        assert!(is_anime(&data(serde_json::json!({ "type": "anime" }))));
+        assert!(is_anime(&data(serde_json::json!({ "meta": { "genres": ["action", "Anime"] } }))));
+        assert!(!is_anime(&data(serde_json::json!({ "type": "show", "meta": { "genres": ["drama"] } }))));
+    }
+}
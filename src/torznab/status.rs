@@ -0,0 +1,37 @@
+use std::sync::Arc;
+use axum::extract::{Query, State};
+use axum::Json;
+use serde::{Deserialize, Serialize};
+use crate::request_clients::circuit_breaker_client::CircuitState;
+use crate::request_clients::orionoid_client::orionoid_request_client::OrionoidRequestClient;
+use crate::request_clients::orionoid_client::quota_tracker::QuotaUsage;
+use crate::torznab::errors::TorznabError;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct StatusForm {
+    pub apikey: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Status {
+    /// Whether requests are currently being sent to Orionoid
+    pub circuit: CircuitState,
+    /// The daily Orionoid quota last reported for the caller's API key, if it has been used yet
+    pub quota: Option<QuotaUsage>,
+}
+
+/// Only ever reports the quota of the caller's own API key, so one user can't see how much others search.
+#[tracing::instrument(skip_all)]
+pub async fn status(
+    State(orionoid_client): State<Arc<OrionoidRequestClient>>,
+    Query(form): Query<StatusForm>,
+) -> Result<Json<Status>, TorznabError> {
+    let Some(apikey) = form.apikey.as_deref().filter(|apikey| !apikey.is_empty()) else {
+        return Err(TorznabError::missing_parameter("apikey"));
+    };
+
+    Ok(Json(Status {
+        circuit: orionoid_client.circuit_state(),
+        quota: orionoid_client.quota_tracker().usage(apikey),
+    }))
+}
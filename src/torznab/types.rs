@@ -3,8 +3,9 @@ use std::collections::HashMap;
 use std::future::Future;
 use std::pin::Pin;
 use std::sync::Arc;
+use crate::torznab::errors::TorznabError;
 
-pub(crate) type SearchFunc = Arc<dyn Fn(SearchParameters) -> Pin<Box<dyn Future<Output = Result<Vec<Torrent>, String>> + Send>> + Send + Sync>;
+pub type SearchFunc = Arc<dyn Fn(SearchParameters) -> Pin<Box<dyn Future<Output = Result<SearchResults, TorznabError>> + Send>> + Send + Sync>;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Limits {
@@ -113,6 +114,14 @@ pub struct SearchParameters {
     pub categories: Option<Vec<u32>>,
     /// The Imdb ID of the item to search for
     pub imdbid: Option<String>,
+    /// The TVDB ID of the item to search for
+    pub tvdbid: Option<String>,
+    /// The TMDB ID of the item to search for
+    pub tmdbid: Option<String>,
+    /// The Trakt ID of the item to search for
+    pub traktid: Option<String>,
+    /// The TVRage ID of the item to search for
+    pub rid: Option<String>,
     /// The season number of the item to search for
     pub season: Option<u32>,
     /// The episode number of the item to search for
@@ -143,6 +152,19 @@ pub struct Torrent {
     pub torrent_file_url: Option<String>,
     /// The magnet URI o the torrent
     pub magnet_uri: Option<String>,
+    /// A stable, unique identifier for the torrent, e.g. its infohash
+    pub guid: Option<String>,
+    /// When the torrent was published, as a unix timestamp in seconds
+    pub publish_date: Option<i64>,
     /// Any other attributes
     pub other_attributes: Option<HashMap<String, String>>,
 }
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+/// Holds a single page of search results
+pub struct SearchResults {
+    /// The torrents on the requested page
+    pub torrents: Vec<Torrent>,
+    /// The total number of results available across all pages, if known
+    pub total: Option<u32>,
+}
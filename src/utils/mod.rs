@@ -1 +1,2 @@
-pub mod empty_strings;
\ No newline at end of file
+pub mod empty_strings;
+pub(crate) mod redaction;
@@ -0,0 +1,48 @@
+//! Keeps Orionoid app and user keys out of logged URLs and errors.
+
+const REDACTED: &str = "REDACTED";
+const SECRET_PARAMS: &[&str] = &["keyapp", "keyuser"];
+
+/// Replaces the value of every secret query parameter in the url.
+pub(crate) fn redact_url(url: &str) -> String {
+    let Some((base, query)) = url.split_once('?') else {
+        return url.to_string();
+    };
+
+    let query = query
+        .split('&')
+        .map(|pair| match pair.split_once('=') {
+            Some((name, _)) if SECRET_PARAMS.contains(&name) => format!("{name}={REDACTED}"),
+            _ => pair.to_string(),
+        })
+        .collect::<Vec<String>>()
+        .join("&");
+
+    format!("{base}?{query}")
+}
+
+/// reqwest includes the request url in its errors, so it has to be redacted before the error is logged or returned.
+pub(crate) fn redact_reqwest_error(err: reqwest::Error) -> reqwest::Error {
+    let Some(url) = err.url() else {
+        return err;
+    };
+
+    match reqwest::Url::parse(&redact_url(url.as_str())) {
+        Ok(redacted) => err.with_url(redacted),
+        Err(_) => err.without_url(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_redact_keys() {
+        assert_eq!(
+            redact_url("https://api.orionoid.com?keyapp=APP&keyuser=SECRET&mode=stream"),
+            "https://api.orionoid.com?keyapp=REDACTED&keyuser=REDACTED&mode=stream",
+        );
+        assert_eq!(redact_url("https://api.orionoid.com/"), "https://api.orionoid.com/");
+    }
+}
@@ -0,0 +1,157 @@
+//! An in-process stand-in for the Orionoid api, answering with recorded fixtures.
+// Each integration test binary only uses some of these helpers
+#![allow(dead_code)]
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use axum::extract::{Query, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::Router;
+use orionznab::request_clients::build_orionoid_request_client;
+use orionznab::{torznab_router, AppConfig, AppState, ConfigurationProvider, OrionoidRequestClient};
+
+pub const MOVIE: &str = include_str!("../fixtures/orionoid/movie.json");
+pub const SHOW: &str = include_str!("../fixtures/orionoid/show.json");
+pub const EPISODE: &str = include_str!("../fixtures/orionoid/episode.json");
+pub const EMPTY_STREAMS: &str = include_str!("../fixtures/orionoid/empty_streams.json");
+pub const USERKEY_ERROR: &str = include_str!("../fixtures/orionoid/userkey_error.json");
+pub const MALFORMED: &str = include_str!("../fixtures/orionoid/malformed.json");
+pub const LATEST_MOVIES: &str = include_str!("../fixtures/orionoid/latest_movies.json");
+pub const LATEST_SHOWS: &str = include_str!("../fixtures/orionoid/latest_shows.json");
+pub const LIMIT_ERROR: &str = include_str!("../fixtures/orionoid/limit_error.json");
+pub const APPKEY_ERROR: &str = include_str!("../fixtures/orionoid/appkey_error.json");
+pub const MAINTENANCE_ERROR: &str = include_str!("../fixtures/orionoid/maintenance_error.json");
+pub const PARAMETER_ERROR: &str = include_str!("../fixtures/orionoid/parameter_error.json");
+pub const SUSPENDED_ERROR: &str = include_str!("../fixtures/orionoid/suspended_error.json");
+pub const NOT_FOUND_ERROR: &str = include_str!("../fixtures/orionoid/notfound_error.json");
+
+/// The api key the fake Orionoid rejects as invalid.
+pub const INVALID_API_KEY: &str = "invalid-key";
+/// Searches for this return a movie without any streams.
+pub const EMPTY_QUERY: &str = "nothing";
+/// Searches for this return a truncated response.
+pub const MALFORMED_QUERY: &str = "malformed";
+/// Searches for this fail with the daily request limit reached.
+pub const LIMIT_QUERY: &str = "limit";
+/// Searches for this fail with an invalid app key.
+pub const APPKEY_QUERY: &str = "appkey";
+/// Searches for this fail with Orionoid under maintenance.
+pub const MAINTENANCE_QUERY: &str = "maintenance";
+/// Searches for this fail with invalid search parameters.
+pub const PARAMETER_QUERY: &str = "parameter";
+/// Searches for this fail with a suspended user account.
+pub const SUSPENDED_QUERY: &str = "suspended";
+/// Searches for this fail with the media not found.
+pub const NOT_FOUND_QUERY: &str = "notfound";
+/// Searches for this fail with a bad gateway status and an html body.
+pub const BAD_GATEWAY_QUERY: &str = "badgateway";
+/// Searches for this return the movie fixture on every page, as if Orionoid had far more results.
+pub const MANY_RESULTS_QUERY: &str = "many";
+
+type RecordedRequests = Arc<Mutex<Vec<HashMap<String, String>>>>;
+
+pub struct MockOrionoid {
+    pub base_url: String,
+    requests: RecordedRequests,
+}
+
+impl MockOrionoid {
+    pub async fn start() -> Self {
+        let requests = RecordedRequests::default();
+        let router = Router::new()
+            .route("/", get(respond))
+            .with_state(requests.clone());
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, router).await });
+
+        Self { base_url, requests }
+    }
+
+    /// The query parameters of every request received so far.
+    pub fn requests(&self) -> Vec<HashMap<String, String>> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+async fn respond(
+    State(requests): State<RecordedRequests>,
+    Query(params): Query<HashMap<String, String>>,
+) -> Response {
+    requests.lock().unwrap().push(params.clone());
+
+    let param = |name: &str| params.get(name).map(String::as_str);
+    let is_latest = ["query", "idimdb", "idtmdb", "idtvdb", "idtrakt", "idtvrage"]
+        .iter()
+        .all(|name| param(name).is_none());
+
+    let error = match param("query") {
+        Some(LIMIT_QUERY) => Some(LIMIT_ERROR),
+        Some(APPKEY_QUERY) => Some(APPKEY_ERROR),
+        Some(MAINTENANCE_QUERY) => Some(MAINTENANCE_ERROR),
+        Some(PARAMETER_QUERY) => Some(PARAMETER_ERROR),
+        Some(SUSPENDED_QUERY) => Some(SUSPENDED_ERROR),
+        Some(NOT_FOUND_QUERY) => Some(NOT_FOUND_ERROR),
+        Some(BAD_GATEWAY_QUERY) => {
+            return (StatusCode::BAD_GATEWAY, [("content-type", "text/html")], "<html>Bad Gateway</html>").into_response();
+        }
+        Some(MANY_RESULTS_QUERY) => {
+            let fixture = MOVIE.replacen(r#""count": { "total": 2"#, r#""count": { "total": 1000"#, 1);
+            return ([("content-type", "application/json")], fixture).into_response();
+        }
+        _ => None,
+    };
+
+    let fixture = if param("keyuser") == Some(INVALID_API_KEY) {
+        USERKEY_ERROR
+    } else if let Some(error) = error {
+        error
+    } else if param("query") == Some(MALFORMED_QUERY) {
+        MALFORMED
+    } else if param("query") == Some(EMPTY_QUERY) {
+        EMPTY_STREAMS
+    } else if is_latest && param("type") == Some("show") {
+        LATEST_SHOWS
+    } else if is_latest {
+        LATEST_MOVIES
+    } else if param("type") == Some("show") && param("numberepisode").is_some() {
+        EPISODE
+    } else if param("type") == Some("show") {
+        SHOW
+    } else {
+        MOVIE
+    };
+
+    ([("content-type", "application/json")], fixture).into_response()
+}
+
+/// The app key the fake Orionoid is called with.
+pub const APP_KEY: &str = "TESTAPPKEY";
+
+/// The default config, pointed at the fake Orionoid.
+pub fn app_config(mock: &MockOrionoid) -> Arc<AppConfig> {
+    let defaults = ConfigurationProvider::default_config().unwrap();
+    Arc::new(AppConfig {
+        orionoid_base_url: mock.base_url.clone(),
+        orionoid_app_key: APP_KEY.to_string(),
+        ..(*defaults).clone()
+    })
+}
+
+pub fn orionoid_client(mock: &MockOrionoid) -> OrionoidRequestClient {
+    build_orionoid_request_client(&app_config(mock)).unwrap()
+}
+
+/// Serves the torznab api over the fake Orionoid, returning its base url.
+pub async fn start_torznab(mock: &MockOrionoid) -> String {
+    let state = AppState::from_config(app_config(mock)).unwrap();
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let base_url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, torznab_router(state)).await });
+
+    base_url
+}
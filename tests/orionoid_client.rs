@@ -0,0 +1,87 @@
+mod common;
+
+use common::{MockOrionoid, APPKEY_QUERY, BAD_GATEWAY_QUERY, INVALID_API_KEY, LIMIT_QUERY, MAINTENANCE_QUERY, NOT_FOUND_QUERY, PARAMETER_QUERY, SUSPENDED_QUERY};
+use orionznab::request_clients::orionoid_client::search_endpoints::{MediaIds, PageRequest};
+use orionznab::request_clients::build_orionoid_request_client;
+use orionznab::request_clients::request_errors::error::Error;
+use orionznab::request_clients::orionoid_client::types::OrionApiResponse;
+use orionznab::{AppConfig, OrionoidRequestClient};
+
+const PAGE: PageRequest = PageRequest { offset: 0, limit: 50 };
+
+async fn search_movie(client: &OrionoidRequestClient, query: &str) -> Result<OrionApiResponse, Error> {
+    client
+        .search_endpoints()
+        .search_movie("user-key", Some(query), MediaIds::default(), PAGE)
+        .await
+}
+
+#[tokio::test]
+async fn should_search_and_track_quota() {
+    let mock = MockOrionoid::start().await;
+    let client = common::orionoid_client(&mock);
+
+    let response = client
+        .search_endpoints()
+        .search_movie("user-key", Some("alien"), MediaIds::default(), PAGE)
+        .await
+        .unwrap();
+
+    let data = response.data.unwrap();
+    assert_eq!(data.movie.unwrap().meta.unwrap().title.as_deref(), Some("Alien"));
+    assert_eq!(data.streams.unwrap().len(), 2);
+    assert_eq!(client.quota_tracker().usage("user-key").unwrap().remaining, Some(988));
+}
+
+#[tokio::test]
+async fn should_map_userkey_errors_to_unauthorized() {
+    let mock = MockOrionoid::start().await;
+    let client = common::orionoid_client(&mock);
+
+    let result = client
+        .search_endpoints()
+        .search_tv(INVALID_API_KEY, Some("breaking bad"), MediaIds::default(), None, None, PAGE)
+        .await;
+
+    assert!(matches!(result, Err(Error::Unauthorized(_))));
+}
+
+#[tokio::test]
+async fn should_map_error_responses_to_typed_errors() {
+    let mock = MockOrionoid::start().await;
+    let client = common::orionoid_client(&mock);
+
+    let search = |query| search_movie(&client, query);
+
+    assert!(matches!(search(SUSPENDED_QUERY).await, Err(Error::AccountSuspended(_))));
+    assert!(matches!(search(NOT_FOUND_QUERY).await, Err(Error::NotFound(_))));
+    assert!(matches!(search(APPKEY_QUERY).await, Err(Error::Server { .. })));
+    assert!(matches!(search(MAINTENANCE_QUERY).await, Err(Error::ServiceUnavailable(_))));
+    assert!(matches!(search(PARAMETER_QUERY).await, Err(Error::Validation(_))));
+    assert!(matches!(search(BAD_GATEWAY_QUERY).await, Err(Error::Server { code: 502, .. })));
+    // Reaching the limit stops every further search with the key, so it goes last
+    assert!(matches!(search(LIMIT_QUERY).await, Err(Error::RequestLimitReached(_))));
+}
+
+#[tokio::test]
+async fn should_only_persist_successful_responses() {
+    let mock = MockOrionoid::start().await;
+    let path = std::env::temp_dir().join(format!("orionznab-{}.redb", uuid::Uuid::new_v4()));
+    let config = AppConfig {
+        response_store_path: Some(path.display().to_string()),
+        ..(*common::app_config(&mock)).clone()
+    };
+    let client = build_orionoid_request_client(&config).unwrap();
+    let search = |query| search_movie(&client, query);
+
+    assert!(search(MAINTENANCE_QUERY).await.is_err());
+    assert!(search(MAINTENANCE_QUERY).await.is_err());
+    assert_eq!(mock.requests().len(), 2);
+
+    search("alien").await.unwrap();
+    search("alien").await.unwrap();
+    assert_eq!(mock.requests().len(), 3);
+
+    drop(client);
+    let _ = std::fs::remove_file(path);
+}
@@ -0,0 +1,489 @@
+mod common;
+
+use common::{
+    MockOrionoid, APPKEY_QUERY, BAD_GATEWAY_QUERY, EMPTY_QUERY, INVALID_API_KEY, LIMIT_QUERY, MAINTENANCE_QUERY,
+    MALFORMED_QUERY, MANY_RESULTS_QUERY, NOT_FOUND_QUERY, PARAMETER_QUERY, SUSPENDED_QUERY,
+};
+
+async fn get(base_url: &str, query: &str) -> (u16, String) {
+    let response = reqwest::get(format!("{base_url}/api?{query}")).await.unwrap();
+    let status = response.status().as_u16();
+    (status, response.text().await.unwrap())
+}
+
+fn item_count(body: &str) -> usize {
+    body.matches("<item>").count()
+}
+
+#[tokio::test]
+async fn should_describe_caps() {
+    let mock = MockOrionoid::start().await;
+    let torznab = common::start_torznab(&mock).await;
+
+    let (status, body) = get(&torznab, "t=caps").await;
+
+    assert_eq!(status, 200);
+    assert!(body.contains("<caps>"));
+    assert!(body.contains(r#"<movie-search available="yes""#));
+    assert!(body.contains(r#"<tv-search available="yes""#));
+    assert!(mock.requests().is_empty());
+}
+
+#[tokio::test]
+async fn should_search_movies() {
+    let mock = MockOrionoid::start().await;
+    let torznab = common::start_torznab(&mock).await;
+
+    let (status, body) = get(&torznab, "t=movie&q=alien&apikey=user-key").await;
+
+    assert_eq!(status, 200);
+    assert_eq!(item_count(&body), 2);
+    assert!(body.contains(r#"<torznab:response offset="0" total="2" />"#));
+    assert!(body.contains("<title>Alien</title>"));
+    assert!(body.contains(r#"<guid isPermaLink="false">8a5f3c2e9b1d4f6a7c0e2b4d6f8a1c3e5b7d9f0a</guid>"#));
+    assert!(body.contains(r#"<torznab:attr infohash="8A5F3C2E9B1D4F6A7C0E2B4D6F8A1C3E5B7D9F0A" />"#));
+    assert!(body.contains(r#"<torznab:attr name="category" value="2040" />"#));
+    assert!(body.contains(r#"<torznab:attr name="category" value="2045" />"#));
+
+    let requests = mock.requests();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0]["type"], "movie");
+    assert_eq!(requests[0]["query"], "alien");
+    assert_eq!(requests[0]["keyuser"], "user-key");
+    assert_eq!(requests[0]["keyapp"], common::APP_KEY);
+}
+
+#[tokio::test]
+async fn should_page_and_count_category_filtered_movies() {
+    let mock = MockOrionoid::start().await;
+    let torznab = common::start_torznab(&mock).await;
+
+    let (status, body) = get(&torznab, "t=movie&q=alien&cat=2045&limit=1&apikey=user-key").await;
+
+    assert_eq!(status, 200);
+    assert_eq!(item_count(&body), 1);
+    assert!(body.contains(r#"<torznab:response offset="0" total="1" />"#));
+    assert!(body.contains(r#"<torznab:attr name="category" value="2045" />"#));
+
+    let (status, body) = get(&torznab, "t=movie&q=alien&cat=2045&limit=1&offset=1&apikey=user-key").await;
+
+    assert_eq!(status, 200);
+    assert_eq!(item_count(&body), 0);
+    assert!(body.contains(r#"<torznab:response offset="1" total="1" />"#));
+}
+
+#[tokio::test]
+async fn should_only_count_filtered_results_paging_can_reach() {
+    let mock = MockOrionoid::start().await;
+    let torznab = common::start_torznab(&mock).await;
+
+    let (status, body) = get(&torznab, &format!("t=movie&q={MANY_RESULTS_QUERY}&cat=2045&limit=1&apikey=user-key")).await;
+
+    assert_eq!(status, 200);
+    assert_eq!(item_count(&body), 1);
+    assert!(body.contains(r#"<torznab:response offset="0" total="999" />"#));
+
+    let (status, body) = get(&torznab, &format!("t=movie&q={MANY_RESULTS_QUERY}&cat=2045&limit=1&offset=10&apikey=user-key")).await;
+
+    assert_eq!(status, 200);
+    assert_eq!(item_count(&body), 0);
+    assert!(body.contains(r#"<torznab:response offset="10" total="5" />"#));
+}
+
+#[tokio::test]
+async fn should_fall_back_to_query_for_empty_ids() {
+    let mock = MockOrionoid::start().await;
+    let torznab = common::start_torznab(&mock).await;
+
+    let (status, body) = get(&torznab, "t=tvsearch&q=breaking%20bad&tvdbid=&rid=&apikey=user-key").await;
+
+    assert_eq!(status, 200);
+    assert_eq!(item_count(&body), 1);
+
+    let request = &mock.requests()[0];
+    assert_eq!(request["query"], "breaking bad");
+    assert!(!request.contains_key("idtvdb"));
+    assert!(!request.contains_key("idtvrage"));
+}
+
+#[tokio::test]
+async fn should_search_movies_and_shows_for_generic_search() {
+    let mock = MockOrionoid::start().await;
+    let torznab = common::start_torznab(&mock).await;
+
+    let (status, body) = get(&torznab, "t=search&q=alien&apikey=user-key").await;
+
+    assert_eq!(status, 200);
+    assert_eq!(item_count(&body), 3);
+    assert!(body.contains(r#"<torznab:response offset="0" total="3" />"#));
+    assert!(body.contains("<title>Alien</title>"));
+    assert!(body.contains("<title>Breaking Bad</title>"));
+
+    let mut types: Vec<String> = mock.requests().iter().map(|request| request["type"].clone()).collect();
+    types.sort();
+    assert_eq!(types, vec!["movie", "show"]);
+}
+
+#[tokio::test]
+async fn should_only_search_requested_categories_for_generic_search() {
+    let mock = MockOrionoid::start().await;
+    let torznab = common::start_torznab(&mock).await;
+
+    let (status, body) = get(&torznab, "t=search&q=breaking%20bad&cat=5000&apikey=user-key").await;
+
+    assert_eq!(status, 200);
+    assert_eq!(item_count(&body), 1);
+    assert!(body.contains("<title>Breaking Bad</title>"));
+
+    let requests = mock.requests();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0]["type"], "show");
+}
+
+#[tokio::test]
+async fn should_only_search_shows_for_generic_search_by_show_ids() {
+    let mock = MockOrionoid::start().await;
+    let torznab = common::start_torznab(&mock).await;
+
+    let (status, body) = get(&torznab, "t=search&tvdbid=81189&apikey=user-key").await;
+
+    assert_eq!(status, 200);
+    assert_eq!(item_count(&body), 1);
+    assert!(body.contains("<title>Breaking Bad</title>"));
+
+    let requests = mock.requests();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0]["type"], "show");
+    assert_eq!(requests[0]["idtvdb"], "81189");
+}
+
+#[tokio::test]
+async fn should_cap_how_far_generic_searches_page() {
+    let mock = MockOrionoid::start().await;
+    let torznab = common::start_torznab(&mock).await;
+
+    let (status, body) = get(&torznab, "t=search&q=alien&offset=100000&limit=100&apikey=user-key").await;
+
+    assert_eq!(status, 200);
+    assert_eq!(item_count(&body), 0);
+    let requests = mock.requests();
+    assert_eq!(requests.len(), 2);
+    for request in requests {
+        assert_eq!(request["limitcount"], "500");
+    }
+}
+
+#[tokio::test]
+async fn should_limit_combined_generic_search_results() {
+    let mock = MockOrionoid::start().await;
+    let torznab = common::start_torznab(&mock).await;
+
+    let (status, body) = get(&torznab, "t=search&q=alien&limit=2&apikey=user-key").await;
+
+    assert_eq!(status, 200);
+    assert_eq!(item_count(&body), 2);
+    assert!(body.contains("<title>Breaking Bad</title>"));
+}
+
+#[tokio::test]
+async fn should_search_shows() {
+    let mock = MockOrionoid::start().await;
+    let torznab = common::start_torznab(&mock).await;
+
+    let (status, body) = get(&torznab, "t=tvsearch&q=breaking%20bad&apikey=user-key").await;
+
+    assert_eq!(status, 200);
+    assert_eq!(item_count(&body), 1);
+    assert!(body.contains("<title>Breaking Bad</title>"));
+    assert!(body.contains("<type>series</type>"));
+    assert!(body.contains(r#"<torznab:attr tvdbid="81189" />"#));
+    assert!(body.contains(r#"<torznab:attr name="category" value="5040" />"#));
+    assert_eq!(mock.requests()[0]["type"], "show");
+}
+
+#[tokio::test]
+async fn should_search_episodes() {
+    let mock = MockOrionoid::start().await;
+    let torznab = common::start_torznab(&mock).await;
+
+    let (status, body) = get(&torznab, "t=tvsearch&tvdbid=81189&season=1&ep=2&apikey=user-key").await;
+
+    assert_eq!(status, 200);
+    assert_eq!(item_count(&body), 1);
+    assert!(body.contains("<title>Breaking Bad - Cat's in the Bag... - S01E02</title>"));
+
+    let request = &mock.requests()[0];
+    assert_eq!(request["idtvdb"], "81189");
+    assert_eq!(request["numberseason"], "1");
+    assert_eq!(request["numberepisode"], "2");
+}
+
+#[tokio::test]
+async fn should_return_empty_feed_without_streams() {
+    let mock = MockOrionoid::start().await;
+    let torznab = common::start_torznab(&mock).await;
+
+    let (status, body) = get(&torznab, &format!("t=movie&q={EMPTY_QUERY}&apikey=user-key")).await;
+
+    assert_eq!(status, 200);
+    assert_eq!(item_count(&body), 0);
+    assert!(body.contains(r#"<torznab:response offset="0" total="0" />"#));
+}
+
+#[tokio::test]
+async fn should_reject_invalid_user_key() {
+    let mock = MockOrionoid::start().await;
+    let torznab = common::start_torznab(&mock).await;
+
+    let (status, body) = get(&torznab, &format!("t=movie&q=alien&apikey={INVALID_API_KEY}")).await;
+
+    assert_eq!(status, 401);
+    assert!(body.contains(r#"<error code="100""#));
+}
+
+#[tokio::test]
+async fn should_report_errors_from_the_only_generic_search_made() {
+    let mock = MockOrionoid::start().await;
+    let torznab = common::start_torznab(&mock).await;
+
+    for query in ["cat=5000&q=breaking%20bad", "tvdbid=81189"] {
+        let (status, body) = get(&torznab, &format!("t=search&{query}&apikey={INVALID_API_KEY}")).await;
+
+        assert_eq!(status, 401);
+        assert!(body.contains(r#"<error code="100""#));
+    }
+}
+
+#[tokio::test]
+async fn should_report_malformed_responses() {
+    let mock = MockOrionoid::start().await;
+    let torznab = common::start_torznab(&mock).await;
+
+    let (status, body) = get(&torznab, &format!("t=movie&q={MALFORMED_QUERY}&apikey=user-key")).await;
+
+    assert_eq!(status, 502);
+    assert!(body.contains(r#"<error code="900""#));
+}
+
+async fn search_error(query: &str) -> (u16, String) {
+    let mock = MockOrionoid::start().await;
+    let torznab = common::start_torznab(&mock).await;
+
+    get(&torznab, &format!("t=movie&q={query}&apikey=user-key")).await
+}
+
+#[tokio::test]
+async fn should_report_request_limit_errors() {
+    let (status, body) = search_error(LIMIT_QUERY).await;
+
+    assert_eq!(status, 429);
+    assert!(body.contains(r#"<error code="500" description="Daily Limit Reached""#));
+}
+
+#[tokio::test]
+async fn should_stop_searching_once_the_daily_limit_is_reached() {
+    let mock = MockOrionoid::start().await;
+    let torznab = common::start_torznab(&mock).await;
+
+    let (status, _) = get(&torznab, &format!("t=movie&q={LIMIT_QUERY}&apikey=user-key")).await;
+    assert_eq!(status, 429);
+
+    let (status, body) = get(&torznab, "t=movie&q=alien&apikey=user-key").await;
+    assert_eq!(status, 429);
+    assert!(body.contains(r#"<error code="500""#));
+    assert_eq!(mock.requests().len(), 1);
+}
+
+#[tokio::test]
+async fn should_report_suspended_accounts() {
+    let (status, body) = search_error(SUSPENDED_QUERY).await;
+
+    assert_eq!(status, 403);
+    assert!(body.contains(r#"<error code="101" description="User Suspended""#));
+}
+
+#[tokio::test]
+async fn should_report_missing_items() {
+    let (status, body) = search_error(NOT_FOUND_QUERY).await;
+
+    assert_eq!(status, 404);
+    assert!(body.contains(r#"<error code="300" description="Media Not Found""#));
+}
+
+#[tokio::test]
+async fn should_report_app_key_errors() {
+    let (status, body) = search_error(APPKEY_QUERY).await;
+
+    assert_eq!(status, 502);
+    assert!(body.contains(r#"<error code="900""#));
+    assert!(body.contains("Invalid App Key"));
+}
+
+#[tokio::test]
+async fn should_report_maintenance_errors() {
+    let (status, body) = search_error(MAINTENANCE_QUERY).await;
+
+    assert_eq!(status, 503);
+    assert!(body.contains("Server Maintenance"));
+}
+
+#[tokio::test]
+async fn should_report_parameter_errors() {
+    let (status, body) = search_error(PARAMETER_QUERY).await;
+
+    assert_eq!(status, 400);
+    assert!(body.contains(r#"<error code="201""#));
+}
+
+#[tokio::test]
+async fn should_report_error_statuses() {
+    let (status, body) = search_error(BAD_GATEWAY_QUERY).await;
+
+    assert_eq!(status, 502);
+    assert!(body.contains(r#"<error code="900""#));
+    assert!(body.contains("502 Bad Gateway"));
+}
+
+#[tokio::test]
+async fn should_require_api_key_for_searches() {
+    let mock = MockOrionoid::start().await;
+    let torznab = common::start_torznab(&mock).await;
+
+    for query in ["t=movie&q=alien", "t=movie&q=alien&apikey=", "t=search&q=alien&apikey=%20"] {
+        let (status, body) = get(&torznab, query).await;
+
+        assert_eq!(status, 400);
+        assert!(body.contains(r#"<error code="200""#));
+    }
+    assert!(mock.requests().is_empty());
+}
+
+#[tokio::test]
+async fn should_cache_repeated_searches() {
+    let mock = MockOrionoid::start().await;
+    let torznab = common::start_torznab(&mock).await;
+
+    get(&torznab, "t=movie&q=alien&apikey=user-key").await;
+    get(&torznab, "t=movie&q=alien&apikey=user-key").await;
+
+    assert_eq!(mock.requests().len(), 1);
+}
+
+#[tokio::test]
+async fn should_encode_search_terms() {
+    let mock = MockOrionoid::start().await;
+    let torznab = common::start_torznab(&mock).await;
+
+    let (status, _) = get(&torznab, "t=movie&q=Fast%20%26%20Furious%20%237%20%2B%20Am%C3%A9lie&apikey=user-key").await;
+
+    assert_eq!(status, 200);
+    let request = &mock.requests()[0];
+    assert_eq!(request["query"], "fast & furious #7 + amélie");
+    assert_eq!(request["keyuser"], "user-key");
+}
+
+#[tokio::test]
+async fn should_cover_unaligned_offsets_with_aligned_pages() {
+    let mock = MockOrionoid::start().await;
+    let torznab = common::start_torznab(&mock).await;
+
+    let (status, body) = get(&torznab, "t=movie&q=alien&offset=3&limit=2&apikey=user-key").await;
+
+    assert_eq!(status, 200);
+    assert_eq!(item_count(&body), 2);
+
+    let pages: Vec<(String, String)> = mock.requests()
+        .iter()
+        .map(|request| (request["limitcount"].clone(), request["limitpage"].clone()))
+        .collect();
+    assert_eq!(pages, vec![("2".to_string(), "2".to_string()), ("2".to_string(), "3".to_string())]);
+}
+
+#[tokio::test]
+async fn should_page_huge_offsets_without_overflowing() {
+    let mock = MockOrionoid::start().await;
+    let torznab = common::start_torznab(&mock).await;
+
+    let (status, _) = get(&torznab, "t=movie&q=alien&offset=4294967290&limit=50&apikey=user-key").await;
+
+    assert_eq!(status, 200);
+    assert_eq!(mock.requests()[0]["limitcount"], "50");
+}
+
+#[tokio::test]
+async fn should_return_latest_movies_without_search_terms() {
+    let mock = MockOrionoid::start().await;
+    let torznab = common::start_torznab(&mock).await;
+
+    let (status, body) = get(&torznab, "t=movie&apikey=user-key").await;
+
+    assert_eq!(status, 200);
+    assert_eq!(item_count(&body), 2);
+    assert!(body.contains("<title>Nosferatu.2024.2160p.WEB-DL.DDP5.1.H.265</title>"));
+    assert!(body.contains("<title>Conclave.2024.1080p.BluRay.x264</title>"));
+
+    let request = &mock.requests()[0];
+    assert!(!request.contains_key("query"));
+    assert_eq!(request["type"], "movie");
+    assert_eq!(request["sortvalue"], "timeadded");
+    assert_eq!(request["sortorder"], "descending");
+}
+
+#[tokio::test]
+async fn should_return_nothing_for_movie_searches_by_show_ids() {
+    let mock = MockOrionoid::start().await;
+    let torznab = common::start_torznab(&mock).await;
+
+    let (status, body) = get(&torznab, "t=movie&tvdbid=371980&apikey=user-key").await;
+
+    assert_eq!(status, 200);
+    assert_eq!(item_count(&body), 0);
+    assert!(mock.requests().is_empty());
+}
+
+#[tokio::test]
+async fn should_fetch_the_latest_releases_on_every_poll() {
+    let mock = MockOrionoid::start().await;
+    let torznab = common::start_torznab(&mock).await;
+
+    get(&torznab, "t=movie&apikey=user-key").await;
+    get(&torznab, "t=movie&apikey=user-key").await;
+
+    assert_eq!(mock.requests().len(), 2);
+}
+
+#[tokio::test]
+async fn should_return_latest_episodes_for_empty_tv_search() {
+    let mock = MockOrionoid::start().await;
+    let torznab = common::start_torznab(&mock).await;
+
+    let (status, body) = get(&torznab, "t=tvsearch&q=&apikey=user-key").await;
+
+    assert_eq!(status, 200);
+    assert_eq!(item_count(&body), 1);
+    assert!(body.contains("<title>Severance.S02E10.1080p.WEB.h264</title>"));
+    assert!(body.contains("<type>series</type>"));
+
+    let request = &mock.requests()[0];
+    assert!(!request.contains_key("query"));
+    assert_eq!(request["type"], "show");
+    assert_eq!(request["sortvalue"], "timeadded");
+}
+
+#[tokio::test]
+async fn should_only_report_the_callers_quota() {
+    let mock = MockOrionoid::start().await;
+    let torznab = common::start_torznab(&mock).await;
+    get(&torznab, "t=movie&q=alien&apikey=user-key").await;
+    get(&torznab, "t=movie&q=alien&apikey=other-key").await;
+
+    let response = reqwest::get(format!("{torznab}/status")).await.unwrap();
+    assert_eq!(response.status(), 400);
+
+    let status: serde_json::Value = reqwest::get(format!("{torznab}/status?apikey=user-key")).await.unwrap().json().await.unwrap();
+    assert_eq!(status["circuit"], "closed");
+    assert_eq!(status["quota"]["remaining"], 988);
+
+    let status: serde_json::Value = reqwest::get(format!("{torznab}/status?apikey=unused-key")).await.unwrap().json().await.unwrap();
+    assert!(status["quota"].is_null());
+}